@@ -2,7 +2,7 @@ extern crate shark;
 
 use crate::shark::shader::ShaderExt;
 use palette::Srgb;
-use shark::shader::{primitives::*, FragOne, FragThree, Shader};
+use shark::shader::{primitives::*, FragThree, Shader};
 
 fn main() {
     // A gradient that goes from off to purple in 5 leds that repeats after every 5 leds.
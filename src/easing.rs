@@ -0,0 +1,239 @@
+//! Easing curves, usable as interpolators for [`position_gradient`] and [`time_gradient`] or as
+//! time warps with [`ShaderExt::remap_time`].
+//!
+//! Every curve maps `0.0` to `0.0` and `1.0` to `1.0`. Inputs outside of that range are not
+//! clamped.
+//!
+//! [`position_gradient`]: crate::shader::primitives::position_gradient
+//! [`time_gradient`]: crate::shader::primitives::time_gradient
+//! [`ShaderExt::remap_time`]: crate::shader::ShaderExt::remap_time
+
+use core::f64::consts::{FRAC_PI_2, PI};
+
+use palette::num::{Powf, Round, Trigonometry};
+
+pub fn linear(t: f64) -> f64 {
+    t
+}
+
+pub fn quad_in(t: f64) -> f64 {
+    t * t
+}
+
+pub fn quad_out(t: f64) -> f64 {
+    1.0 - quad_in(1.0 - t)
+}
+
+pub fn quad_in_out(t: f64) -> f64 {
+    in_out(t, quad_in)
+}
+
+pub fn cubic_in(t: f64) -> f64 {
+    t * t * t
+}
+
+pub fn cubic_out(t: f64) -> f64 {
+    1.0 - cubic_in(1.0 - t)
+}
+
+pub fn cubic_in_out(t: f64) -> f64 {
+    in_out(t, cubic_in)
+}
+
+pub fn sine_in(t: f64) -> f64 {
+    1.0 - Trigonometry::cos(t * FRAC_PI_2)
+}
+
+pub fn sine_out(t: f64) -> f64 {
+    Trigonometry::sin(t * FRAC_PI_2)
+}
+
+pub fn sine_in_out(t: f64) -> f64 {
+    (1.0 - Trigonometry::cos(t * PI)) / 2.0
+}
+
+pub fn expo_in(t: f64) -> f64 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        Powf::powf(2.0, 10.0 * t - 10.0)
+    }
+}
+
+pub fn expo_out(t: f64) -> f64 {
+    1.0 - expo_in(1.0 - t)
+}
+
+pub fn expo_in_out(t: f64) -> f64 {
+    in_out(t, expo_in)
+}
+
+pub fn elastic_in(t: f64) -> f64 {
+    1.0 - elastic_out(1.0 - t)
+}
+
+pub fn elastic_out(t: f64) -> f64 {
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else {
+        Powf::powf(2.0, -10.0 * t) * Trigonometry::sin((t * 10.0 - 0.75) * (2.0 * PI / 3.0)) + 1.0
+    }
+}
+
+pub fn elastic_in_out(t: f64) -> f64 {
+    in_out(t, elastic_in)
+}
+
+pub fn bounce_in(t: f64) -> f64 {
+    1.0 - bounce_out(1.0 - t)
+}
+
+pub fn bounce_out(t: f64) -> f64 {
+    const N: f64 = 7.5625;
+    const D: f64 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+pub fn bounce_in_out(t: f64) -> f64 {
+    in_out(t, bounce_in)
+}
+
+/// Runs `ease_in` over the first half and its mirror image over the second.
+fn in_out(t: f64, ease_in: fn(f64) -> f64) -> f64 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+/// A CSS style cubic bezier curve from `(0, 0)` to `(1, 1)` with control points `(x1, y1)` and
+/// `(x2, y2)`. `x1` and `x2` should be within `[0, 1]` so that the curve is a function of `t`.
+pub fn cubic_bezier(
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+) -> impl Fn(f64) -> f64 + Copy + Send + Sync {
+    // Polynomial coefficients of each coordinate of the curve.
+    let coefficients = |p1: f64, p2: f64| {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        let a = 1.0 - c - b;
+        (a, b, c)
+    };
+    let (ax, bx, cx) = coefficients(x1, x2);
+    let (ay, by, cy) = coefficients(y1, y2);
+
+    let sample_x = move |s: f64| ((ax * s + bx) * s + cx) * s;
+    let sample_dx = move |s: f64| (3.0 * ax * s + 2.0 * bx) * s + cx;
+
+    move |t: f64| {
+        // Solve x(s) = t with Newton's method, falling back to bisection when the slope is flat.
+        let mut s = t;
+        for _ in 0..8 {
+            let error = sample_x(s) - t;
+            if error.abs() < 1e-7 {
+                return ((ay * s + by) * s + cy) * s;
+            }
+            let slope = sample_dx(s);
+            if slope.abs() < 1e-6 {
+                break;
+            }
+            s -= error / slope;
+        }
+
+        let (mut low, mut high) = (0.0, 1.0);
+        s = t.clamp(0.0, 1.0);
+        for _ in 0..32 {
+            let x = sample_x(s);
+            if (x - t).abs() < 1e-7 {
+                break;
+            } else if x < t {
+                low = s;
+            } else {
+                high = s;
+            }
+            s = (low + high) / 2.0;
+        }
+        ((ay * s + by) * s + cy) * s
+    }
+}
+
+/// Jumps between `steps` evenly spaced levels instead of moving smoothly, holding each level until
+/// the next one is reached.
+pub fn steps(steps: u32) -> impl Fn(f64) -> f64 + Copy + Send + Sync {
+    let steps = steps.max(1) as f64;
+    move |t: f64| {
+        if t >= 1.0 {
+            1.0
+        } else {
+            Round::floor(t * steps) / steps
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints() {
+        let curves: [fn(f64) -> f64; 19] = [
+            linear,
+            quad_in,
+            quad_out,
+            quad_in_out,
+            cubic_in,
+            cubic_out,
+            cubic_in_out,
+            sine_in,
+            sine_out,
+            sine_in_out,
+            expo_in,
+            expo_out,
+            expo_in_out,
+            elastic_in,
+            elastic_out,
+            elastic_in_out,
+            bounce_in,
+            bounce_out,
+            bounce_in_out,
+        ];
+        for curve in curves {
+            assert!(curve(0.0).abs() < 1e-3);
+            assert!((curve(1.0) - 1.0).abs() < 1e-3);
+        }
+        assert!((cubic_in_out(0.5) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bezier_and_steps() {
+        let ease = cubic_bezier(0.25, 0.1, 0.25, 1.0);
+        assert!((ease(0.5) - 0.8024).abs() < 1e-3);
+        assert!(ease(0.0).abs() < 1e-6);
+        assert!((ease(1.0) - 1.0).abs() < 1e-6);
+
+        let linear = cubic_bezier(0.0, 0.0, 1.0, 1.0);
+        assert!((linear(0.3) - 0.3).abs() < 1e-6);
+
+        let stepped = steps(4);
+        assert_eq!(stepped(0.3), 0.25);
+        assert_eq!(stepped(0.99), 0.75);
+        assert_eq!(stepped(1.0), 1.0);
+    }
+}
@@ -0,0 +1,209 @@
+use palette::{IntoColor, LinSrgb, Srgb};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The output buffer cannot hold the encoded frame.
+    BufferTooSmall { required: usize, available: usize },
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall {
+                required,
+                available,
+            } => write!(
+                f,
+                "output buffer holds {available} bytes but the frame needs {required}"
+            ),
+        }
+    }
+}
+
+/// Converts a rendered frame into the bytes an LED strip expects on the wire.
+pub trait Encoder {
+    /// Number of bytes needed to encode a frame of `leds` pixels.
+    fn frame_len(&self, leds: usize) -> usize;
+
+    /// Encodes `colors` into the start of `out`, returning the number of bytes written.
+    fn encode<C: IntoColor<LinSrgb<f64>> + Clone>(
+        &self,
+        colors: &[C],
+        out: &mut [u8],
+    ) -> Result<usize, EncodeError>;
+}
+
+fn check_len(required: usize, out: &[u8]) -> Result<(), EncodeError> {
+    if out.len() < required {
+        Err(EncodeError::BufferTooSmall {
+            required,
+            available: out.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn to_srgb8(color: LinSrgb<f64>) -> Srgb<u8> {
+    Srgb::from_linear(color)
+}
+
+/// WS2812(B) style strips: 3 gamma-corrected bytes per pixel in GRB order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ws2812;
+
+impl Encoder for Ws2812 {
+    fn frame_len(&self, leds: usize) -> usize {
+        leds * 3
+    }
+
+    fn encode<C: IntoColor<LinSrgb<f64>> + Clone>(
+        &self,
+        colors: &[C],
+        out: &mut [u8],
+    ) -> Result<usize, EncodeError> {
+        let required = self.frame_len(colors.len());
+        check_len(required, out)?;
+
+        for (color, bytes) in colors.iter().zip(out.chunks_exact_mut(3)) {
+            let color = to_srgb8(color.clone().into_color());
+            bytes.copy_from_slice(&[color.green, color.red, color.blue]);
+        }
+        Ok(required)
+    }
+}
+
+/// SK6812 RGBW strips: 4 gamma-corrected bytes per pixel in GRBW order.
+#[derive(Debug, Clone, Copy)]
+pub struct Sk6812 {
+    /// Move the part shared by all three channels onto the white LED. When disabled the white
+    /// channel is left off.
+    pub extract_white: bool,
+}
+
+impl Default for Sk6812 {
+    fn default() -> Self {
+        Self {
+            extract_white: true,
+        }
+    }
+}
+
+impl Encoder for Sk6812 {
+    fn frame_len(&self, leds: usize) -> usize {
+        leds * 4
+    }
+
+    fn encode<C: IntoColor<LinSrgb<f64>> + Clone>(
+        &self,
+        colors: &[C],
+        out: &mut [u8],
+    ) -> Result<usize, EncodeError> {
+        let required = self.frame_len(colors.len());
+        check_len(required, out)?;
+
+        for (color, bytes) in colors.iter().zip(out.chunks_exact_mut(4)) {
+            let mut color: LinSrgb<f64> = color.clone().into_color();
+            let white = if self.extract_white {
+                // Extracting in linear space keeps the emitted light the same.
+                let white = color.red.min(color.green).min(color.blue).max(0.0);
+                color -= LinSrgb::new(white, white, white);
+                white
+            } else {
+                0.0
+            };
+
+            let color = to_srgb8(color);
+            let white = to_srgb8(LinSrgb::new(white, white, white)).red;
+            bytes.copy_from_slice(&[color.green, color.red, color.blue, white]);
+        }
+        Ok(required)
+    }
+}
+
+/// APA102 (and SK9822) strips: a zero start frame, 4 bytes per pixel carrying the 5-bit global
+/// brightness followed by BGR, then an end frame of ones to clock the data through.
+#[derive(Debug, Clone, Copy)]
+pub struct Apa102 {
+    /// Global brightness from 0 to 31. Larger values are clamped.
+    pub brightness: u8,
+}
+
+impl Default for Apa102 {
+    fn default() -> Self {
+        Self { brightness: 31 }
+    }
+}
+
+impl Apa102 {
+    const START_FRAME_LEN: usize = 4;
+
+    fn end_frame_len(leds: usize) -> usize {
+        // Half a clock per LED is needed to push the data through, but SK9822 clones need a full
+        // 32 bits regardless.
+        leds.div_ceil(16).max(4)
+    }
+}
+
+impl Encoder for Apa102 {
+    fn frame_len(&self, leds: usize) -> usize {
+        Self::START_FRAME_LEN + leds * 4 + Self::end_frame_len(leds)
+    }
+
+    fn encode<C: IntoColor<LinSrgb<f64>> + Clone>(
+        &self,
+        colors: &[C],
+        out: &mut [u8],
+    ) -> Result<usize, EncodeError> {
+        let required = self.frame_len(colors.len());
+        check_len(required, out)?;
+
+        let (start, rest) = out.split_at_mut(Self::START_FRAME_LEN);
+        let (pixels, rest) = rest.split_at_mut(colors.len() * 4);
+        let end = &mut rest[..Self::end_frame_len(colors.len())];
+
+        start.fill(0x00);
+        let brightness = 0xE0 | self.brightness.min(31);
+        for (color, bytes) in colors.iter().zip(pixels.chunks_exact_mut(4)) {
+            let color = to_srgb8(color.clone().into_color());
+            bytes.copy_from_slice(&[brightness, color.blue, color.green, color.red]);
+        }
+        end.fill(0xFF);
+
+        Ok(required)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use palette::LinSrgb;
+
+    use super::{Apa102, EncodeError, Encoder, Sk6812, Ws2812};
+
+    #[test]
+    fn wire_formats() {
+        let frame = [LinSrgb::new(1.0, 0.0, 0.0), LinSrgb::new(1.0, 1.0, 0.5)];
+        let mut out = [0u8; 32];
+
+        assert_eq!(Ws2812.encode(&frame, &mut out), Ok(6));
+        assert_eq!(out[..6], [0, 255, 0, 255, 255, 188]);
+
+        assert_eq!(Sk6812::default().encode(&frame, &mut out), Ok(8));
+        assert_eq!(out[4..8], [188, 188, 0, 188]);
+
+        let apa102 = Apa102 { brightness: 40 };
+        assert_eq!(apa102.encode(&frame, &mut out), Ok(16));
+        assert_eq!(
+            out[..16],
+            [0, 0, 0, 0, 0xFF, 0, 0, 255, 0xFF, 188, 255, 255, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+
+        assert_eq!(
+            Ws2812.encode(&frame, &mut out[..4]),
+            Err(EncodeError::BufferTooSmall {
+                required: 6,
+                available: 4
+            })
+        );
+    }
+}
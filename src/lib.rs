@@ -1,12 +1,16 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![feature(generic_const_exprs, test)]
+#![allow(incomplete_features)]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 #[cfg(feature = "std")]
 extern crate test;
 
+pub mod easing;
+pub mod encode;
 pub mod point;
+pub mod render;
 pub mod shader;
 
 #[cfg(test)]
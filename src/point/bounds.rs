@@ -0,0 +1,138 @@
+//! Measuring sets of points and mapping them into unit space, so that shaders written for
+//! coordinates in `[0, 1]` look the same on layouts measured in any units.
+
+use super::Point;
+
+/// Axis-aligned box around a set of points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+/// How [`Bounds::normalize`] fits points into the unit cube.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Normalization {
+    /// Stretches every axis to `[0, 1]` on its own.
+    #[default]
+    PerAxis,
+    /// Scales every axis alike so the longest spans `[0, 1]`, centering the others on `0.5`.
+    Uniform,
+}
+
+impl Bounds {
+    /// The smallest box containing every point, or `None` if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, point| {
+            Some(match bounds {
+                None => Bounds {
+                    min: point,
+                    max: point,
+                },
+                Some(Bounds { min, max }) => Bounds {
+                    min: Point::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z)),
+                    max: Point::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z)),
+                },
+            })
+        })
+    }
+
+    pub fn size(&self) -> Point {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Point {
+        self.min.lerp(self.max, 0.5)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    /// Maps `point` from the box into the unit cube. Axes the box is flat along map to `0.5`.
+    pub fn normalize(&self, point: Point, normalization: Normalization) -> Point {
+        let size = self.size();
+        let offset = point - self.center();
+        let scale = |extent: f64| if extent > 0.0 { 1.0 / extent } else { 0.0 };
+        let scale = match normalization {
+            Normalization::PerAxis => Point::new(scale(size.x), scale(size.y), scale(size.z)),
+            Normalization::Uniform => {
+                let scale = scale(size.x.max(size.y).max(size.z));
+                Point::new(scale, scale, scale)
+            }
+        };
+        offset * scale + Point::new(0.5, 0.5, 0.5)
+    }
+}
+
+pub fn bounds(points: impl IntoIterator<Item = Point>) -> Option<Bounds> {
+    Bounds::from_points(points)
+}
+
+/// Mean position of the points, or `None` if there are none.
+pub fn centroid(points: impl IntoIterator<Item = Point>) -> Option<Point> {
+    let (sum, count) = points
+        .into_iter()
+        .fold((Point::default(), 0usize), |(sum, count), point| {
+            (sum + point, count + 1)
+        });
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Length of the path running through the points in order.
+pub fn arc_length(points: impl IntoIterator<Item = Point>) -> f64 {
+    let mut points = points.into_iter();
+    let Some(first) = points.next() else {
+        return 0.0;
+    };
+    points
+        .fold((0.0, first), |(length, previous), point| {
+            (length + previous.distance(point), point)
+        })
+        .0
+}
+
+/// Points of a layout mapped into the unit cube, in the same order.
+#[derive(Debug, Clone)]
+pub struct Normalized<I: Iterator<Item = Point>> {
+    points: I,
+    bounds: Option<Bounds>,
+    normalization: Normalization,
+}
+
+impl<I: Iterator<Item = Point>> Iterator for Normalized<I> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let point = self.points.next()?;
+        // There are no bounds only if there are no points, so this always has some.
+        Some(self.bounds?.normalize(point, self.normalization))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.points.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator<Item = Point>> ExactSizeIterator for Normalized<I> {}
+
+/// Fits a layout into the unit cube, walking it once up front to measure it.
+///
+/// For example `normalize(ring(center, 30.0, 0.0, 60), Normalization::Uniform)` lights the same
+/// as a ring of radius `0.5` around `(0.5, 0.5)`.
+pub fn normalize<I: IntoIterator<Item = Point>>(
+    points: I,
+    normalization: Normalization,
+) -> Normalized<I::IntoIter>
+where
+    I::IntoIter: Clone,
+{
+    let points = points.into_iter();
+    Normalized {
+        bounds: Bounds::from_points(points.clone()),
+        points,
+        normalization,
+    }
+}
@@ -0,0 +1,273 @@
+use core::ops::Range;
+
+use alloc::vec::Vec;
+
+use super::{
+    bounds::{self, Bounds, Normalization},
+    Point,
+};
+use crate::shader::{primitives::Affine, Attributes};
+
+/// One physical run of LEDs in a [`Layout`], such as a strip up one arm of the robot.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    points: Vec<Point>,
+    reversed: bool,
+    transform: Affine<3>,
+    /// Sorted positions of the dead pixels in wiring order.
+    dead: Vec<usize>,
+}
+
+impl Segment {
+    /// A segment with one LED at each point, wired in order.
+    pub fn new(points: impl IntoIterator<Item = Point>) -> Self {
+        Segment {
+            points: points.into_iter().collect(),
+            reversed: false,
+            transform: Affine::identity(),
+            dead: Vec::new(),
+        }
+    }
+
+    /// Wires the segment from its last point to its first, e.g. for a strip coming back down.
+    pub fn reversed(self) -> Self {
+        Segment {
+            reversed: !self.reversed,
+            ..self
+        }
+    }
+
+    /// Moves the points of the segment, after any transforms that were already added.
+    pub fn with_transform(self, transform: Affine<3>) -> Self {
+        Segment {
+            transform: self.transform.then(&transform),
+            ..self
+        }
+    }
+
+    /// Leaves the LEDs at these positions in the wiring order of the segment unlit, such as
+    /// broken or covered pixels. They still take up an index.
+    pub fn with_dead_pixels(mut self, dead: impl IntoIterator<Item = usize>) -> Self {
+        self.dead.extend(dead);
+        // Kept sorted so `Layout::push` can binary search it.
+        self.dead.sort_unstable();
+        self.dead.dedup();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+/// A lit LED of a [`Layout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pixel {
+    pub point: Point,
+    pub attributes: Attributes,
+}
+
+/// Several segments daisy-chained into one addressable strip.
+///
+/// Every LED in the chain gets a global index. LEDs that should not be lit, whether dead pixels
+/// of a segment or skipped between segments, are kept as `None` so the rest stay at their index.
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+    pixels: Vec<Option<Pixel>>,
+    segments: Vec<Range<usize>>,
+    /// Positions of the dead pixels of pushed segments by global index, so `u` can be measured
+    /// through them again.
+    dead: Vec<(usize, Point)>,
+}
+
+/// Sets `u` of the lit pixels of a segment to their distance along it over its whole length.
+///
+/// `points` has the position of every LED of the segment in wiring order, or `None` where it is
+/// not known, in which case the segment is measured straight past it.
+fn measure_u(pixels: &mut [Option<Pixel>], points: impl Iterator<Item = Option<Point>> + Clone) {
+    let length = bounds::arc_length(points.clone().flatten());
+    let mut distance = 0.0;
+    let mut previous = None;
+    for (pixel, point) in pixels.iter_mut().zip(points) {
+        let Some(point) = point else {
+            continue;
+        };
+        if let Some(previous) = previous {
+            distance += point.distance(previous);
+        }
+        previous = Some(point);
+        if let Some(pixel) = pixel {
+            pixel.attributes.u = Some(if length > 0.0 { distance / length } else { 0.0 });
+        }
+    }
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn from_parts(pixels: Vec<Option<Pixel>>, segments: Vec<Range<usize>>) -> Self {
+        Layout {
+            pixels,
+            segments,
+            dead: Vec::new(),
+        }
+    }
+
+    /// Chains `segment` onto the end of the layout, returning the global indices of its LEDs.
+    ///
+    /// LEDs are given the index of the segment as their strip id, and their distance along the
+    /// segment in wiring order as `u`.
+    pub fn push(&mut self, segment: Segment) -> Range<usize> {
+        let start = self.pixels.len();
+        let strip = self.segments.len();
+
+        let mut points: Vec<Point> = segment
+            .points
+            .iter()
+            .map(|point| Point::from(segment.transform.apply((*point).into())))
+            .collect();
+        if segment.reversed {
+            points.reverse();
+        }
+
+        for (i, &point) in points.iter().enumerate() {
+            if segment.dead.binary_search(&i).is_ok() {
+                self.pixels.push(None);
+                self.dead.push((start + i, point));
+                continue;
+            }
+            self.pixels.push(Some(Pixel {
+                point,
+                attributes: Attributes {
+                    index: Some(start + i),
+                    strip: Some(strip),
+                    u: None,
+                },
+            }));
+        }
+
+        let range = start..self.pixels.len();
+        measure_u(
+            &mut self.pixels[range.clone()],
+            points.iter().copied().map(Some),
+        );
+        self.segments.push(range.clone());
+        range
+    }
+
+    pub fn with_segment(mut self, segment: Segment) -> Self {
+        self.push(segment);
+        self
+    }
+
+    /// Adds `count` unlit LEDs to the chain, such as the ones hidden in the wiring between two
+    /// segments.
+    pub fn skip(&mut self, count: usize) {
+        self.pixels.extend((0..count).map(|_| None));
+    }
+
+    pub fn with_skipped(mut self, count: usize) -> Self {
+        self.skip(count);
+        self
+    }
+
+    /// Total number of LEDs in the chain, lit or not.
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    /// Global index range of each segment, in the order they were chained.
+    pub fn segments(&self) -> &[Range<usize>] {
+        &self.segments
+    }
+
+    /// Every LED of the chain by global index, with `None` for unlit ones.
+    pub fn pixels(&self) -> &[Option<Pixel>] {
+        &self.pixels
+    }
+
+    /// Positions of the lit LEDs.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.pixels.iter().flatten().map(|pixel| pixel.point)
+    }
+
+    /// Box around the lit LEDs, or `None` if there are none.
+    pub fn bounds(&self) -> Option<Bounds> {
+        Bounds::from_points(self.points())
+    }
+
+    pub fn centroid(&self) -> Option<Point> {
+        bounds::centroid(self.points())
+    }
+
+    /// Total length of the segments, following the lit LEDs of each in wiring order.
+    pub fn arc_length(&self) -> f64 {
+        self.segments
+            .iter()
+            .map(|range| {
+                bounds::arc_length(
+                    self.pixels[range.clone()]
+                        .iter()
+                        .flatten()
+                        .map(|pixel| pixel.point),
+                )
+            })
+            .sum()
+    }
+
+    /// The same layout fitted into the unit cube, keeping every index and strip id.
+    ///
+    /// [`Normalization::PerAxis`] changes the shape of each segment, so `u` is measured again
+    /// along the normalized segment, the same way as [`Layout::push`] does.
+    /// [`Normalization::Uniform`] keeps it.
+    pub fn normalized(&self, normalization: Normalization) -> Layout {
+        let Some(bounds) = self.bounds() else {
+            return self.clone();
+        };
+        let mut pixels: Vec<Option<Pixel>> = self
+            .pixels
+            .iter()
+            .map(|pixel| {
+                pixel.map(|pixel| Pixel {
+                    point: bounds.normalize(pixel.point, normalization),
+                    ..pixel
+                })
+            })
+            .collect();
+        let dead: Vec<(usize, Point)> = self
+            .dead
+            .iter()
+            .map(|&(index, point)| (index, bounds.normalize(point, normalization)))
+            .collect();
+        if normalization == Normalization::PerAxis {
+            for range in &self.segments {
+                let points: Vec<Option<Point>> = range
+                    .clone()
+                    .map(|index| match pixels[index] {
+                        Some(pixel) => Some(pixel.point),
+                        None => dead
+                            .binary_search_by_key(&index, |(index, _)| *index)
+                            .ok()
+                            .map(|i| dead[i].1),
+                    })
+                    .collect();
+                measure_u(&mut pixels[range.clone()], points.into_iter());
+            }
+        }
+        Layout {
+            pixels,
+            segments: self.segments.clone(),
+            dead,
+        }
+    }
+}
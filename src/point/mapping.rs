@@ -0,0 +1,483 @@
+//! Loading and saving [`Layout`]s as lists of LED positions, such as the ones exported from CAD.
+//!
+//! CSV files have one LED per line. An optional header names the columns, out of `x`, `y`, `z`,
+//! `strip`, `index`, `u` and `dead`; without one the columns are `x`, `y` and optionally `z`.
+//! Empty lines and lines starting with `#` are skipped, and every column except `x` and `y` may
+//! be left empty.
+//!
+//! LEDs without an index follow the one before them, and LEDs without a strip id are on strip 0.
+//! Indices that no LED claims are left unlit. Dead LEDs, with `dead` set to 1, are unlit too but
+//! keep their place, and are outside every strip unless they have a strip id. With the `serde`
+//! feature, the same records can be read from and written to JSON.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::ops::Range;
+
+use super::{
+    layout::{Layout, Pixel},
+    Point,
+};
+use crate::shader::Attributes;
+
+/// One LED of a mapping file.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LedRecord {
+    pub x: f64,
+    pub y: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub z: f64,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub strip: Option<usize>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub index: Option<usize>,
+    /// Distance along the strip from 0 to 1, worked out from the positions when left out.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub u: Option<f64>,
+    /// An unlit LED that only holds its index and strip, such as a dead pixel. Its position is
+    /// ignored.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
+    pub dead: bool,
+}
+
+#[cfg(feature = "serde")]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MappingErrorKind {
+    /// The CSV header does not name a required column.
+    MissingColumn(&'static str),
+    UnknownColumn(String),
+    WrongFieldCount {
+        expected: usize,
+        found: usize,
+    },
+    InvalidNumber {
+        column: &'static str,
+        value: String,
+    },
+    DuplicateIndex(usize),
+    /// The index is far past the number of LEDs in the file, see [`Layout::from_records`].
+    IndexOutOfRange {
+        index: usize,
+        limit: usize,
+    },
+    /// The LEDs of a strip are interleaved with the LEDs of another.
+    OverlappingStrips {
+        strip: usize,
+    },
+    /// The JSON could not be parsed into records.
+    Json(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MappingError {
+    pub kind: MappingErrorKind,
+    /// Line of the source the error is on, or for records passed in directly, their position in
+    /// the list. Both count from 1.
+    pub row: usize,
+}
+
+impl core::fmt::Display for MappingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "row {}: ", self.row)?;
+        match &self.kind {
+            MappingErrorKind::MissingColumn(column) => write!(f, "missing column `{column}`"),
+            MappingErrorKind::UnknownColumn(column) => write!(f, "unknown column `{column}`"),
+            MappingErrorKind::WrongFieldCount { expected, found } => {
+                write!(f, "expected {expected} fields, found {found}")
+            }
+            MappingErrorKind::InvalidNumber { column, value } => {
+                write!(f, "invalid {column} value {value:?}")
+            }
+            MappingErrorKind::DuplicateIndex(index) => {
+                write!(f, "index {index} is used more than once")
+            }
+            MappingErrorKind::IndexOutOfRange { index, limit } => {
+                write!(
+                    f,
+                    "index {index} is out of range, indices must be below {limit}"
+                )
+            }
+            MappingErrorKind::OverlappingStrips { strip } => {
+                write!(f, "strip {strip} overlaps another strip")
+            }
+            MappingErrorKind::Json(message) => f.write_str(message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    X,
+    Y,
+    Z,
+    Strip,
+    Index,
+    U,
+    Dead,
+}
+
+impl Column {
+    const ALL: [Column; 7] = [
+        Column::X,
+        Column::Y,
+        Column::Z,
+        Column::Strip,
+        Column::Index,
+        Column::U,
+        Column::Dead,
+    ];
+
+    fn named(name: &str) -> Option<Column> {
+        Column::ALL
+            .into_iter()
+            .find(|column| column.name().eq_ignore_ascii_case(name))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Column::X => "x",
+            Column::Y => "y",
+            Column::Z => "z",
+            Column::Strip => "strip",
+            Column::Index => "index",
+            Column::U => "u",
+            Column::Dead => "dead",
+        }
+    }
+}
+
+/// Reads CSV records, paired with the line they were on.
+fn parse_csv(source: &str) -> Result<Vec<(usize, LedRecord)>, MappingError> {
+    let mut header: Option<Vec<Column>> = None;
+    let mut records = Vec::new();
+
+    for (row, line) in source.lines().enumerate() {
+        let row = row + 1;
+        let error = |kind| MappingError { kind, row };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+
+        // A typo in the first LED must not turn it into a header, so a header either names only
+        // columns or, misspelling some, has no numbers at all.
+        let is_header = header.is_none()
+            && records.is_empty()
+            && (fields.iter().all(|field| Column::named(field).is_some())
+                || fields.iter().all(|field| field.parse::<f64>().is_err()));
+        if is_header {
+            let mut columns = Vec::with_capacity(fields.len());
+            for field in &fields {
+                let column = Column::named(field)
+                    .ok_or_else(|| error(MappingErrorKind::UnknownColumn(field.to_string())))?;
+                columns.push(column);
+            }
+            for required in [Column::X, Column::Y] {
+                if !columns.contains(&required) {
+                    return Err(error(MappingErrorKind::MissingColumn(required.name())));
+                }
+            }
+            header = Some(columns);
+            continue;
+        }
+
+        let (columns, required) = match &header {
+            Some(columns) => (columns.as_slice(), columns.len()),
+            None => (&Column::ALL[..3], 2),
+        };
+        if fields.len() < required || fields.len() > columns.len() {
+            return Err(error(MappingErrorKind::WrongFieldCount {
+                expected: columns.len(),
+                found: fields.len(),
+            }));
+        }
+
+        let mut record = LedRecord::default();
+        for (&column, field) in columns.iter().zip(&fields) {
+            let invalid = || {
+                error(MappingErrorKind::InvalidNumber {
+                    column: column.name(),
+                    value: field.to_string(),
+                })
+            };
+            let number = || field.parse::<f64>().map_err(|_| invalid());
+            let integer = || field.parse::<usize>().map_err(|_| invalid());
+
+            let optional = !matches!(column, Column::X | Column::Y);
+            if optional && field.is_empty() {
+                continue;
+            }
+            match column {
+                Column::X => record.x = number()?,
+                Column::Y => record.y = number()?,
+                Column::Z => record.z = number()?,
+                Column::Strip => record.strip = Some(integer()?),
+                Column::Index => record.index = Some(integer()?),
+                Column::U => record.u = Some(number()?),
+                Column::Dead => {
+                    record.dead = match *field {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(invalid()),
+                    }
+                }
+            }
+        }
+        records.push((row, record));
+    }
+
+    Ok(records)
+}
+
+pub fn read_csv(source: &str) -> Result<Vec<LedRecord>, MappingError> {
+    Ok(parse_csv(source)?
+        .into_iter()
+        .map(|(_, record)| record)
+        .collect())
+}
+
+/// Writes records as CSV with a header naming every column.
+pub fn write_csv(records: &[LedRecord]) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+
+    let mut csv = String::from("x,y,z,strip,index,u,dead\n");
+    for record in records {
+        csv += &format!(
+            "{},{},{},{},{},{},{}\n",
+            record.x,
+            record.y,
+            record.z,
+            optional(record.strip.map(|strip| strip.to_string())),
+            optional(record.index.map(|index| index.to_string())),
+            optional(record.u.map(|u| u.to_string())),
+            if record.dead { "1" } else { "" },
+        );
+    }
+    csv
+}
+
+#[cfg(feature = "serde")]
+pub fn read_json(source: &str) -> Result<Vec<LedRecord>, MappingError> {
+    serde_json::from_str(source).map_err(|error| MappingError {
+        row: error.line(),
+        kind: MappingErrorKind::Json(error.to_string()),
+    })
+}
+
+#[cfg(feature = "serde")]
+pub fn write_json(records: &[LedRecord]) -> String {
+    serde_json::to_string_pretty(records).expect("LED records are always valid JSON")
+}
+
+/// A strip of the file, covering every index from its first LED to its last.
+struct StripRange {
+    strip: usize,
+    range: Range<usize>,
+    row: usize,
+}
+
+/// How many times more indices than LEDs a file may use, leaving the rest unlit, before the
+/// indices are taken to be wrong. Small files may use indices up to [`MIN_INDEX_LIMIT`].
+const INDEX_LIMIT_FACTOR: usize = 4;
+const MIN_INDEX_LIMIT: usize = 1024;
+
+/// The strip a record is on, if any.
+fn strip_of(record: &LedRecord) -> Option<usize> {
+    match record.strip {
+        Some(strip) => Some(strip),
+        None if record.dead => None,
+        None => Some(0),
+    }
+}
+
+fn build(rows: Vec<(usize, LedRecord)>) -> Result<Layout, MappingError> {
+    // Every index gets a pixel, so a stray huge index must not be allowed to allocate.
+    let limit = rows
+        .len()
+        .saturating_mul(INDEX_LIMIT_FACTOR)
+        .max(MIN_INDEX_LIMIT);
+    let mut next_index = 0;
+    let mut leds: Vec<(usize, usize, LedRecord)> = Vec::with_capacity(rows.len());
+    for (row, record) in rows {
+        let index = record.index.unwrap_or(next_index);
+        if index >= limit {
+            return Err(MappingError {
+                kind: MappingErrorKind::IndexOutOfRange { index, limit },
+                row,
+            });
+        }
+        next_index = index + 1;
+        leds.push((index, row, record));
+    }
+    leds.sort_by_key(|(index, _, _)| *index);
+
+    if let Some(pair) = leds.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(MappingError {
+            kind: MappingErrorKind::DuplicateIndex(pair[1].0),
+            row: pair[0].1.max(pair[1].1),
+        });
+    }
+
+    let mut strips: Vec<StripRange> = Vec::new();
+    for &(index, row, record) in &leds {
+        let Some(strip) = strip_of(&record) else {
+            continue;
+        };
+        match strips.iter_mut().find(|range| range.strip == strip) {
+            Some(range) => range.range.end = index + 1,
+            None => strips.push(StripRange {
+                strip,
+                range: index..index + 1,
+                row,
+            }),
+        }
+    }
+    strips.sort_by_key(|strip| strip.range.start);
+    if let Some(pair) = strips
+        .windows(2)
+        .find(|pair| pair[0].range.end > pair[1].range.start)
+    {
+        return Err(MappingError {
+            kind: MappingErrorKind::OverlappingStrips {
+                strip: pair[1].strip,
+            },
+            row: pair[1].row,
+        });
+    }
+
+    let len = leds.last().map_or(0, |(index, _, _)| index + 1);
+    let mut pixels = vec![None; len];
+    for strip in &strips {
+        let on_strip: Vec<&(usize, usize, LedRecord)> = leds
+            .iter()
+            .filter(|(_, _, record)| !record.dead && strip_of(record) == Some(strip.strip))
+            .collect();
+
+        // Distance of each LED from the start of the strip, for the ones without a `u`.
+        let mut distances = Vec::with_capacity(on_strip.len());
+        let mut distance = 0.0;
+        let mut previous: Option<Point> = None;
+        for (_, _, record) in &on_strip {
+            let point = Point::new(record.x, record.y, record.z);
+            if let Some(previous) = previous {
+                distance += previous.distance(point);
+            }
+            previous = Some(point);
+            distances.push(distance);
+        }
+        let length = distance;
+
+        for ((index, _, record), distance) in on_strip.into_iter().zip(distances) {
+            let computed = if length > 0.0 { distance / length } else { 0.0 };
+            pixels[*index] = Some(Pixel {
+                point: Point::new(record.x, record.y, record.z),
+                attributes: Attributes {
+                    index: Some(*index),
+                    strip: Some(strip.strip),
+                    u: Some(record.u.unwrap_or(computed)),
+                },
+            });
+        }
+    }
+
+    let segments = strips.into_iter().map(|strip| strip.range).collect();
+    Ok(Layout::from_parts(pixels, segments))
+}
+
+impl Layout {
+    /// Builds a layout from LED records.
+    ///
+    /// Indices must be below 4 times the number of records, or 1024 for small files, so that a
+    /// mistyped index cannot allocate a huge layout.
+    pub fn from_records(records: &[LedRecord]) -> Result<Layout, MappingError> {
+        build(
+            records
+                .iter()
+                .enumerate()
+                .map(|(i, record)| (i + 1, *record))
+                .collect(),
+        )
+    }
+
+    pub fn from_csv(source: &str) -> Result<Layout, MappingError> {
+        build(parse_csv(source)?)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(source: &str) -> Result<Layout, MappingError> {
+        Layout::from_records(&read_json(source)?)
+    }
+
+    /// Every LED, with its index, strip id and `u`, so that loading the records again gives
+    /// back the same layout. Dead and skipped LEDs are written as [`LedRecord::dead`].
+    pub fn records(&self) -> Vec<LedRecord> {
+        // Dead LEDs have no strip id of their own, so they take the one of the lit LEDs around
+        // them, or for a segment without any, its position.
+        let segment_strips: Vec<usize> = self
+            .segments()
+            .iter()
+            .enumerate()
+            .map(|(segment, range)| {
+                self.pixels()[range.clone()]
+                    .iter()
+                    .flatten()
+                    .find_map(|pixel| pixel.attributes.strip)
+                    .unwrap_or(segment)
+            })
+            .collect();
+
+        self.pixels()
+            .iter()
+            .enumerate()
+            .map(|(index, pixel)| match pixel {
+                Some(pixel) => LedRecord {
+                    x: pixel.point.x,
+                    y: pixel.point.y,
+                    z: pixel.point.z,
+                    strip: pixel.attributes.strip,
+                    index: pixel.attributes.index,
+                    u: pixel.attributes.u,
+                    dead: false,
+                },
+                None => LedRecord {
+                    strip: self
+                        .segments()
+                        .iter()
+                        .position(|range| range.contains(&index))
+                        .map(|segment| segment_strips[segment]),
+                    index: Some(index),
+                    dead: true,
+                    ..LedRecord::default()
+                },
+            })
+            .collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        write_csv(&self.records())
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        write_json(&self.records())
+    }
+}
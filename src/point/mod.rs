@@ -1,6 +1,13 @@
+pub mod bounds;
+#[cfg(feature = "alloc")]
+pub mod layout;
+#[cfg(feature = "alloc")]
+pub mod mapping;
 pub mod primitives;
 
-#[derive(Clone, Copy, Debug, Default)]
+use palette::num::Sqrt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 // So that it can be exported to the visualizer.
 #[repr(C)]
 pub struct Point {
@@ -13,6 +20,45 @@ impl Point {
     pub fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
     }
+
+    pub fn dot(self, other: Point) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Point) -> Point {
+        Point {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn length_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f64 {
+        Sqrt::sqrt(self.length_squared())
+    }
+
+    pub fn distance(self, other: Point) -> f64 {
+        (other - self).length()
+    }
+
+    /// The point scaled to a length of 1, or the origin if it has no length.
+    pub fn normalize(self) -> Point {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self / length
+        }
+    }
+
+    /// Linearly interpolates from `self` at `t = 0` to `other` at `t = 1`.
+    pub fn lerp(self, other: Point, t: f64) -> Point {
+        (1.0 - t) * self + t * other
+    }
 }
 
 impl core::ops::Add for Point {
@@ -66,6 +112,83 @@ impl core::ops::Mul<Point> for f64 {
         rhs * self
     }
 }
+impl core::ops::Div for Point {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x / rhs.x,
+            y: self.y / rhs.y,
+            z: self.z / rhs.z,
+        }
+    }
+}
+impl core::ops::Div<f64> for Point {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Self {
+            x: self.x / rhs,
+            y: self.y / rhs,
+            z: self.z / rhs,
+        }
+    }
+}
+impl core::ops::Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+macro_rules! assign_op {
+    ($trait:ident, $func_name:ident, $op:tt, $rhs:ty) => {
+        impl core::ops::$trait<$rhs> for Point {
+            fn $func_name(&mut self, rhs: $rhs) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+assign_op!(AddAssign, add_assign, +, Point);
+assign_op!(SubAssign, sub_assign, -, Point);
+assign_op!(MulAssign, mul_assign, *, Point);
+assign_op!(MulAssign, mul_assign, *, f64);
+assign_op!(DivAssign, div_assign, /, Point);
+assign_op!(DivAssign, div_assign, /, f64);
+
+/// Conversions to and from fragment position arrays, dropping the trailing axes or padding them
+/// with zeros.
+macro_rules! array_conversion {
+    ($($dimensions:literal),+) => {
+        $(
+            impl From<[f64; $dimensions]> for Point {
+                fn from(pos: [f64; $dimensions]) -> Self {
+                    let mut components = [0.0; 3];
+                    components[..$dimensions].copy_from_slice(&pos);
+                    let [x, y, z] = components;
+                    Point { x, y, z }
+                }
+            }
+
+            impl From<Point> for [f64; $dimensions] {
+                fn from(point: Point) -> Self {
+                    let mut pos = [0.0; $dimensions];
+                    pos.copy_from_slice(&[point.x, point.y, point.z][..$dimensions]);
+                    pos
+                }
+            }
+        )+
+    };
+}
+
+array_conversion!(1, 2, 3);
 
 #[cfg(all(test, feature = "alloc"))]
 pub mod tests {
@@ -86,4 +209,309 @@ pub mod tests {
 
         let points: Vec<Point> = points.collect();
     }
+
+    #[test]
+    fn point_math() {
+        let a = Point::new(1.0, 2.0, 2.0);
+        let b = Point::new(0.0, 1.0, 0.0);
+
+        assert_eq!(a.length(), 3.0);
+        assert_eq!(a.distance(b), Point::new(1.0, 1.0, 2.0).length());
+        assert_eq!(a.dot(b), 2.0);
+        assert_eq!(
+            Point::new(1.0, 0.0, 0.0).cross(b),
+            Point::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(a.normalize().length(), 1.0);
+        assert_eq!(Point::default().normalize(), Point::default());
+        assert_eq!(a.lerp(b, 0.5), Point::new(0.5, 1.5, 1.0));
+        assert_eq!(-a / 2.0, Point::new(-0.5, -1.0, -1.0));
+
+        let mut c = a;
+        c += b;
+        c *= 2.0;
+        c -= a;
+        assert_eq!(c, Point::new(1.0, 4.0, 2.0));
+
+        assert_eq!(<[f64; 3]>::from(a), [1.0, 2.0, 2.0]);
+        assert_eq!(<[f64; 2]>::from(a), [1.0, 2.0]);
+        assert_eq!(Point::from([4.0]), Point::new(4.0, 0.0, 0.0));
+
+        let line = super::primitives::Line::new(Point::default(), Point::new(1.0, 1.0, 1.0), 2);
+        assert_eq!(
+            line.collect::<Vec<_>>(),
+            [Point::default(), Point::new(1.0, 1.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        use super::{
+            bounds::{arc_length, bounds, centroid, normalize, Bounds, Normalization},
+            layout::{Layout, Segment},
+            primitives::{line, rectangle},
+        };
+
+        let points = rectangle(Point::new(-2.0, 1.0, 0.0), 4.0, 2.0, 6);
+        let bounds = bounds(points.clone()).unwrap();
+        assert_eq!(bounds.min, Point::new(-2.0, 1.0, 0.0));
+        assert_eq!(bounds.max, Point::new(2.0, 3.0, 0.0));
+        assert_eq!(bounds.center(), Point::new(0.0, 2.0, 0.0));
+        assert!(bounds.contains(Point::new(1.0, 1.5, 0.0)));
+        assert_eq!(centroid(points.clone()), Some(Point::new(0.0, 2.0, 0.0)));
+        assert_eq!(arc_length(points.clone()), 10.0);
+        assert_eq!(Bounds::from_points(core::iter::empty()), None);
+        assert_eq!(centroid(core::iter::empty()), None);
+
+        let per_axis: Vec<Point> = normalize(points.clone(), Normalization::PerAxis).collect();
+        assert_eq!(per_axis[0], Point::new(0.0, 0.0, 0.5));
+        assert_eq!(per_axis[3], Point::new(1.0, 1.0, 0.5));
+        let normalized = normalize(points, Normalization::Uniform);
+        assert_eq!(normalized.len(), 6);
+        let uniform: Vec<Point> = normalized.collect();
+        assert_eq!(uniform[0], Point::new(0.0, 0.25, 0.5));
+        assert_eq!(uniform[3], Point::new(1.0, 0.75, 0.5));
+
+        // The same strip measured in meters and in centimeters.
+        let meters = Layout::new()
+            .with_segment(Segment::new(line(
+                Point::default(),
+                Point::new(2.0, 1.0, 0.0),
+                3,
+            )))
+            .with_segment(Segment::new(line(
+                Point::default(),
+                Point::new(0.0, 1.0, 0.0),
+                2,
+            )));
+        let centimeters = Layout::new()
+            .with_segment(Segment::new(line(
+                Point::default(),
+                Point::new(200.0, 100.0, 0.0),
+                3,
+            )))
+            .with_segment(Segment::new(line(
+                Point::default(),
+                Point::new(0.0, 100.0, 0.0),
+                2,
+            )));
+        assert_eq!(
+            meters.arc_length(),
+            Point::new(2.0, 1.0, 0.0).length() + 1.0
+        );
+        assert_eq!(meters.centroid(), Some(Point::new(0.6, 0.5, 0.0)));
+        assert_eq!(
+            meters.normalized(Normalization::Uniform).pixels(),
+            centimeters.normalized(Normalization::Uniform).pixels()
+        );
+        assert_eq!(
+            meters.normalized(Normalization::PerAxis).pixels()[2]
+                .unwrap()
+                .point,
+            Point::new(1.0, 1.0, 0.5)
+        );
+
+        // Stretching the short leg makes both legs the same length.
+        let corner = Layout::new().with_segment(Segment::new([
+            Point::default(),
+            Point::new(2.0, 0.0, 0.0),
+            Point::new(2.0, 1.0, 0.0),
+        ]));
+        let u = |layout: &Layout| layout.pixels()[1].unwrap().attributes.u.unwrap();
+        assert!((u(&corner) - 2.0 / 3.0).abs() < 1e-12);
+        assert!((u(&corner.normalized(Normalization::Uniform)) - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(u(&corner.normalized(Normalization::PerAxis)), 0.5);
+
+        // A square layout keeps its shape per axis, so `u` is unchanged, measured through the
+        // dead pixel as it was when the segment was pushed.
+        let detour = Layout::new().with_segment(
+            Segment::new([
+                Point::default(),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(2.0, 0.0, 0.0),
+                Point::new(2.0, 2.0, 0.0),
+            ])
+            .with_dead_pixels([1]),
+        );
+        let u = |layout: &Layout| layout.pixels()[2].unwrap().attributes.u.unwrap();
+        let expected = 2.0 * 2.0_f64.sqrt() / (2.0 * 2.0_f64.sqrt() + 2.0);
+        assert!((u(&detour) - expected).abs() < 1e-12);
+        assert!((u(&detour.normalized(Normalization::PerAxis)) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn layouts() {
+        use super::primitives::{arc, helix, polyline, rectangle, ring, serpentine, spiral};
+
+        let close = |a: Point, b: Point| {
+            (a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9 && (a.z - b.z).abs() < 1e-9
+        };
+
+        let points: Vec<Point> = arc(Point::default(), 2.0, 0.0, 180.0, 3).collect();
+        assert!(close(points[1], Point::new(0.0, 2.0, 0.0)));
+        assert!(close(points[2], Point::new(-2.0, 0.0, 0.0)));
+
+        let ring = ring(Point::new(0.0, 0.0, 1.0), 1.0, 90.0, 4);
+        assert_eq!(ring.len(), 4);
+        let points: Vec<Point> = ring.collect();
+        assert!(close(points[1], Point::new(-1.0, 0.0, 1.0)));
+
+        let points: Vec<Point> = rectangle(Point::default(), 3.0, 1.0, 8).collect();
+        assert!(close(points[2], Point::new(2.0, 0.0, 0.0)));
+        assert!(close(points[4], Point::new(3.0, 1.0, 0.0)));
+        assert!(close(points[7], Point::new(0.0, 1.0, 0.0)));
+
+        let points: Vec<Point> = spiral(Point::default(), 0.0, 2.0, 1.0, 5).collect();
+        assert!(close(points[4], Point::new(2.0, 0.0, 0.0)));
+        let points: Vec<Point> = helix(Point::default(), 1.0, 4.0, 2.0, 9).collect();
+        assert!(close(points[8], Point::new(1.0, 0.0, 8.0)));
+
+        let points: Vec<Point> = serpentine(Point::default(), 3, 2, (1.0, 2.0)).collect();
+        assert!(close(points[2], Point::new(2.0, 0.0, 0.0)));
+        assert!(close(points[3], Point::new(2.0, 2.0, 0.0)));
+
+        let vertices = [
+            Point::default(),
+            Point::new(4.0, 0.0, 0.0),
+            Point::new(4.0, 2.0, 0.0),
+        ];
+        let path = polyline(&vertices, [2, 1]);
+        assert_eq!(path.len(), 3);
+        let points: Vec<Point> = path.collect();
+        assert!(close(points[0], Point::new(1.0, 0.0, 0.0)));
+        assert!(close(points[2], Point::new(4.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn mapping_files() {
+        use super::{
+            layout::{Layout, Segment},
+            mapping::{read_csv, MappingErrorKind},
+            primitives::{line, ring},
+        };
+
+        let layout = Layout::new()
+            .with_segment(Segment::new(line(
+                Point::default(),
+                Point::new(0.0, 3.0, 0.0),
+                4,
+            )))
+            .with_skipped(1)
+            .with_segment(Segment::new(ring(Point::default(), 1.0, 0.0, 5)).reversed());
+
+        let loaded = Layout::from_csv(&layout.to_csv()).unwrap();
+        assert_eq!(loaded.len(), layout.len());
+        assert_eq!(loaded.segments(), layout.segments());
+        for (loaded, original) in loaded.pixels().iter().zip(layout.pixels()) {
+            match (loaded, original) {
+                (Some(loaded), Some(original)) => {
+                    assert_eq!(loaded.attributes, original.attributes);
+                    assert_eq!(
+                        (loaded.point.x, loaded.point.y, loaded.point.z),
+                        (original.point.x, original.point.y, original.point.z)
+                    );
+                }
+                (loaded, original) => assert_eq!(loaded.is_none(), original.is_none()),
+            }
+        }
+
+        // Dead LEDs at the ends of a strip and LEDs skipped after the last one keep their place.
+        let dead_ends = Layout::new()
+            .with_segment(
+                Segment::new(line(Point::default(), Point::new(0.0, 3.0, 0.0), 4))
+                    .with_dead_pixels([0, 3]),
+            )
+            .with_skipped(1)
+            .with_segment(
+                Segment::new(line(Point::default(), Point::new(2.0, 0.0, 0.0), 3))
+                    .with_dead_pixels([2]),
+            )
+            .with_skipped(2);
+        let loaded = Layout::from_csv(&dead_ends.to_csv()).unwrap();
+        assert_eq!(loaded.len(), 10);
+        assert_eq!(loaded.segments(), dead_ends.segments());
+        assert_eq!(loaded.records(), dead_ends.records());
+
+        let csv = "# measured\nx, y\n0, 0\n\n0, 2\n0, 4\n";
+        let loaded = Layout::from_csv(csv).unwrap();
+        let pixel = loaded.pixels()[1].unwrap();
+        assert_eq!((pixel.point.y, pixel.attributes.u), (2.0, Some(0.5)));
+
+        let error = read_csv("x,y,z\n1,2,3\n1,two,3\n").unwrap_err();
+        assert_eq!(error.row, 3);
+        assert_eq!(
+            error.kind,
+            MappingErrorKind::InvalidNumber {
+                column: "y",
+                value: "two".into()
+            }
+        );
+        // A typo in the first LED of a file without a header is not taken for a header.
+        let error = read_csv("1,2,x3\n4,5,6\n").unwrap_err();
+        assert_eq!(
+            (error.kind, error.row),
+            (
+                MappingErrorKind::InvalidNumber {
+                    column: "z",
+                    value: "x3".into()
+                },
+                1
+            )
+        );
+        assert_eq!(
+            read_csv("x,y,w\n").unwrap_err().kind,
+            MappingErrorKind::UnknownColumn("w".into())
+        );
+        assert_eq!(
+            read_csv("x,y,dead\n0,0,2\n").unwrap_err().kind,
+            MappingErrorKind::InvalidNumber {
+                column: "dead",
+                value: "2".into()
+            }
+        );
+        assert_eq!(
+            read_csv("1,2,3,4\n").unwrap_err().kind,
+            MappingErrorKind::WrongFieldCount {
+                expected: 3,
+                found: 4
+            }
+        );
+        let error = Layout::from_csv("x,y,index\n0,0,1\n0,1,0\n0,2,1\n").unwrap_err();
+        assert_eq!(
+            (error.kind, error.row),
+            (MappingErrorKind::DuplicateIndex(1), 4)
+        );
+        let error = Layout::from_csv("x,y,strip\n0,0,0\n0,1,1\n0,2,0\n").unwrap_err();
+        assert_eq!(error.kind, MappingErrorKind::OverlappingStrips { strip: 1 });
+        let error = Layout::from_csv("x,y,index\n0,0,0\n0,1,18446744073709551615\n").unwrap_err();
+        assert_eq!(
+            (error.kind, error.row),
+            (
+                MappingErrorKind::IndexOutOfRange {
+                    index: usize::MAX,
+                    limit: 1024
+                },
+                3
+            )
+        );
+        assert_eq!(
+            Layout::from_csv("x,y,index\n0,0,1023\n").unwrap().len(),
+            1024
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let loaded = Layout::from_json(&layout.to_json()).unwrap();
+            assert_eq!(loaded.records(), layout.records());
+            let loaded = Layout::from_json(&dead_ends.to_json()).unwrap();
+            assert_eq!(loaded.segments(), dead_ends.segments());
+            assert_eq!(loaded.records(), dead_ends.records());
+            let loaded = Layout::from_json(r#"[{"x": 1, "y": 2}, {"x": 1, "y": 3, "index": 3}]"#);
+            assert_eq!(loaded.unwrap().len(), 4);
+            assert!(matches!(
+                Layout::from_json("[{\"y\": 2}]").unwrap_err().kind,
+                MappingErrorKind::Json(_)
+            ));
+        }
+    }
 }
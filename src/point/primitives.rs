@@ -1,3 +1,5 @@
+use palette::num::Trigonometry;
+
 use super::Point;
 
 #[derive(Debug, Clone)]
@@ -34,8 +36,311 @@ impl Iterator for Line {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.num_points.saturating_sub(self.current_point);
+        (remaining, Some(remaining))
+    }
 }
 
+impl ExactSizeIterator for Line {}
+
 pub fn line(a: Point, b: Point, num_points: usize) -> Line {
     Line::new(a, b, num_points)
 }
+
+/// Implements [`Iterator`] and [`ExactSizeIterator`] for a layout that can compute its `i`th
+/// point directly.
+macro_rules! indexed_layout {
+    ($name:ident) => {
+        impl Iterator for $name {
+            type Item = Point;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.current_point >= self.num_points {
+                    return None;
+                }
+                let point = self.point(self.current_point);
+                self.current_point += 1;
+                Some(point)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let remaining = self.num_points - self.current_point.min(self.num_points);
+                (remaining, Some(remaining))
+            }
+        }
+
+        impl ExactSizeIterator for $name {}
+    };
+}
+
+/// Points around a circle in the plane of `center` parallel to xy. Angles are in degrees,
+/// counterclockwise from the +x axis.
+#[derive(Debug, Clone)]
+pub struct Arc {
+    pub center: Point,
+    pub radius: f64,
+    pub start_angle: f64,
+    /// Angle between neighboring points.
+    pub step: f64,
+    pub num_points: usize,
+    current_point: usize,
+}
+impl Arc {
+    pub fn new(center: Point, radius: f64, start_angle: f64, step: f64, num_points: usize) -> Self {
+        Self {
+            center,
+            radius,
+            start_angle,
+            step,
+            num_points,
+            current_point: 0,
+        }
+    }
+
+    fn point(&self, i: usize) -> Point {
+        let angle = (self.start_angle + self.step * i as f64).to_radians();
+        self.center
+            + Point::new(
+                self.radius * Trigonometry::cos(angle),
+                self.radius * Trigonometry::sin(angle),
+                0.0,
+            )
+    }
+}
+indexed_layout!(Arc);
+
+/// An arc from `start_angle` to `end_angle` degrees, with points on both ends.
+pub fn arc(center: Point, radius: f64, start_angle: f64, end_angle: f64, num_points: usize) -> Arc {
+    let step = if num_points > 1 {
+        (end_angle - start_angle) / (num_points - 1) as f64
+    } else {
+        0.0
+    };
+    Arc::new(center, radius, start_angle, step, num_points)
+}
+
+/// A closed ring of points, the first at `start_angle` degrees and the rest evenly spaced
+/// counterclockwise.
+pub fn ring(center: Point, radius: f64, start_angle: f64, num_points: usize) -> Arc {
+    let step = 360.0 / num_points.max(1) as f64;
+    Arc::new(center, radius, start_angle, step, num_points)
+}
+
+/// A closed loop of points evenly spaced around the perimeter of an axis-aligned rectangle in the
+/// plane of `corner` parallel to xy, starting at `corner` and going counterclockwise.
+#[derive(Debug, Clone)]
+pub struct Rectangle {
+    pub corner: Point,
+    pub width: f64,
+    pub height: f64,
+    pub num_points: usize,
+    current_point: usize,
+}
+impl Rectangle {
+    pub fn new(corner: Point, width: f64, height: f64, num_points: usize) -> Self {
+        Self {
+            corner,
+            width,
+            height,
+            num_points,
+            current_point: 0,
+        }
+    }
+
+    fn point(&self, i: usize) -> Point {
+        let perimeter = 2.0 * (self.width + self.height);
+        let distance = perimeter * i as f64 / self.num_points as f64;
+        let (x, y) = if distance < self.width {
+            (distance, 0.0)
+        } else if distance < self.width + self.height {
+            (self.width, distance - self.width)
+        } else if distance < 2.0 * self.width + self.height {
+            (2.0 * self.width + self.height - distance, self.height)
+        } else {
+            (0.0, perimeter - distance)
+        };
+        self.corner + Point::new(x, y, 0.0)
+    }
+}
+indexed_layout!(Rectangle);
+
+pub fn rectangle(corner: Point, width: f64, height: f64, num_points: usize) -> Rectangle {
+    Rectangle::new(corner, width, height, num_points)
+}
+
+/// Points winding `turns` times counterclockwise around an axis through `center` parallel to z,
+/// while the radius changes linearly from `start_radius` to `end_radius` and z rises by `pitch`
+/// per turn. Points are evenly spaced in angle, not in distance.
+#[derive(Debug, Clone)]
+pub struct Spiral {
+    pub center: Point,
+    pub start_radius: f64,
+    pub end_radius: f64,
+    pub turns: f64,
+    pub pitch: f64,
+    pub num_points: usize,
+    current_point: usize,
+}
+impl Spiral {
+    pub fn new(
+        center: Point,
+        start_radius: f64,
+        end_radius: f64,
+        turns: f64,
+        pitch: f64,
+        num_points: usize,
+    ) -> Self {
+        Self {
+            center,
+            start_radius,
+            end_radius,
+            turns,
+            pitch,
+            num_points,
+            current_point: 0,
+        }
+    }
+
+    fn point(&self, i: usize) -> Point {
+        let t = if self.num_points > 1 {
+            i as f64 / (self.num_points - 1) as f64
+        } else {
+            0.0
+        };
+        let radius = self.start_radius + (self.end_radius - self.start_radius) * t;
+        let angle = (360.0 * self.turns * t).to_radians();
+        self.center
+            + Point::new(
+                radius * Trigonometry::cos(angle),
+                radius * Trigonometry::sin(angle),
+                self.pitch * self.turns * t,
+            )
+    }
+}
+indexed_layout!(Spiral);
+
+/// A flat spiral, from `start_radius` at the center out to `end_radius`.
+pub fn spiral(
+    center: Point,
+    start_radius: f64,
+    end_radius: f64,
+    turns: f64,
+    num_points: usize,
+) -> Spiral {
+    Spiral::new(center, start_radius, end_radius, turns, 0.0, num_points)
+}
+
+/// A helix of constant `radius`, rising by `pitch` per turn, such as a strip wrapped around a
+/// tube.
+pub fn helix(center: Point, radius: f64, pitch: f64, turns: f64, num_points: usize) -> Spiral {
+    Spiral::new(center, radius, radius, turns, pitch, num_points)
+}
+
+/// A grid of `columns` by `rows` points wired in a zig-zag, as in most LED matrices: the first row
+/// runs along +x from `origin`, the next back along -x one row up in y, and so on.
+#[derive(Debug, Clone)]
+pub struct Serpentine {
+    pub origin: Point,
+    pub columns: usize,
+    pub rows: usize,
+    pub spacing: (f64, f64),
+    num_points: usize,
+    current_point: usize,
+}
+impl Serpentine {
+    pub fn new(origin: Point, columns: usize, rows: usize, spacing: (f64, f64)) -> Self {
+        Self {
+            origin,
+            columns,
+            rows,
+            spacing,
+            num_points: columns * rows,
+            current_point: 0,
+        }
+    }
+
+    fn point(&self, i: usize) -> Point {
+        let row = i / self.columns;
+        let column = match row % 2 {
+            0 => i % self.columns,
+            _ => self.columns - 1 - i % self.columns,
+        };
+        self.origin
+            + Point::new(
+                column as f64 * self.spacing.0,
+                row as f64 * self.spacing.1,
+                0.0,
+            )
+    }
+}
+indexed_layout!(Serpentine);
+
+pub fn serpentine(origin: Point, columns: usize, rows: usize, spacing: (f64, f64)) -> Serpentine {
+    Serpentine::new(origin, columns, rows, spacing)
+}
+
+/// Points along a path through `vertices`, with `counts[i]` points on the segment from
+/// `vertices[i]` to `vertices[i + 1]`.
+///
+/// Each point sits in the middle of its share of the segment, so no two segments put a point on
+/// the same corner. Takes anything that can be borrowed as slices, so it works without allocating.
+#[derive(Debug, Clone)]
+pub struct Polyline<V: AsRef<[Point]>, C: AsRef<[usize]>> {
+    pub vertices: V,
+    pub counts: C,
+    segment: usize,
+    current_point: usize,
+}
+impl<V: AsRef<[Point]>, C: AsRef<[usize]>> Polyline<V, C> {
+    pub fn new(vertices: V, counts: C) -> Self {
+        Self {
+            vertices,
+            counts,
+            segment: 0,
+            current_point: 0,
+        }
+    }
+
+    fn segments(&self) -> usize {
+        let vertices = self.vertices.as_ref().len().saturating_sub(1);
+        vertices.min(self.counts.as_ref().len())
+    }
+}
+
+impl<V: AsRef<[Point]>, C: AsRef<[usize]>> Iterator for Polyline<V, C> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.segment < self.segments() {
+            let count = self.counts.as_ref()[self.segment];
+            if self.current_point < count {
+                let vertices = self.vertices.as_ref();
+                let (a, b) = (vertices[self.segment], vertices[self.segment + 1]);
+                let t = (self.current_point as f64 + 0.5) / count as f64;
+                self.current_point += 1;
+                return Some((1.0 - t) * a + t * b);
+            }
+            self.segment += 1;
+            self.current_point = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let counts = &self.counts.as_ref()[..self.segments()];
+        let remaining = counts
+            .iter()
+            .skip(self.segment)
+            .sum::<usize>()
+            .saturating_sub(self.current_point);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<V: AsRef<[Point]>, C: AsRef<[usize]>> ExactSizeIterator for Polyline<V, C> {}
+
+pub fn polyline<V: AsRef<[Point]>, C: AsRef<[usize]>>(vertices: V, counts: C) -> Polyline<V, C> {
+    Polyline::new(vertices, counts)
+}
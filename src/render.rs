@@ -0,0 +1,528 @@
+use palette::{LinSrgb, Srgb};
+
+#[cfg(feature = "alloc")]
+use crate::point::layout::Layout;
+use crate::{
+    point::{bounds::arc_length, Point},
+    shader::{
+        primitives::BATCH_CHUNK, Attributes, Frag, FragOne, FragThree, FragTwo, Shader, Vertex,
+    },
+};
+
+/// A fragment that can be built from a point of an LED layout.
+pub trait FromPoint: Vertex {
+    fn from_point(point: Point, time: f64) -> Self;
+
+    /// Like [`FromPoint::from_point`], for fragment types that can also carry [`Attributes`].
+    /// The rest drop them.
+    fn from_point_with(point: Point, time: f64, attributes: Attributes) -> Self {
+        let _ = attributes;
+        Self::from_point(point, time)
+    }
+}
+
+impl FromPoint for FragOne {
+    fn from_point(point: Point, time: f64) -> Self {
+        FragOne {
+            pos: [point.x],
+            time,
+        }
+    }
+}
+
+impl FromPoint for FragTwo {
+    fn from_point(point: Point, time: f64) -> Self {
+        FragTwo {
+            pos: [point.x, point.y],
+            time,
+        }
+    }
+}
+
+impl FromPoint for FragThree {
+    fn from_point(point: Point, time: f64) -> Self {
+        FragThree {
+            pos: [point.x, point.y, point.z],
+            time,
+        }
+    }
+}
+
+/// Takes as many of x, y and z as there are dimensions, padding any further ones with zeros.
+impl<const D: usize> FromPoint for Frag<D> {
+    fn from_point(point: Point, time: f64) -> Self {
+        let mut pos = [0.0; D];
+        for (target, component) in pos.iter_mut().zip([point.x, point.y, point.z]) {
+            *target = component;
+        }
+        Frag::new(pos, time)
+    }
+
+    fn from_point_with(point: Point, time: f64, attributes: Attributes) -> Self {
+        Self::from_point(point, time).with_attributes(attributes)
+    }
+}
+
+fn render_with<F: FromPoint, S: Shader<F>, C>(
+    shader: &S,
+    points: impl IntoIterator<Item = Point>,
+    time: f64,
+    out: &mut [C],
+    convert: impl Fn(LinSrgb<f64>) -> C,
+    mut u: impl FnMut(usize, Point) -> Option<f64>,
+) -> usize {
+    let pixels = points.into_iter().enumerate().map(|(index, point)| {
+        let attributes = Attributes {
+            index: Some(index),
+            strip: None,
+            u: u(index, point),
+        };
+        Some((point, attributes))
+    });
+    render_pixels(shader, pixels, time, out, convert)
+}
+
+/// `u` of each point as its index over the last index, if `points` knows how many there are.
+fn index_u(points: &impl Iterator<Item = Point>) -> impl FnMut(usize, Point) -> Option<f64> {
+    let len = match points.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(lower),
+        _ => None,
+    };
+    move |index, _| {
+        len.map(|len| {
+            if len > 1 {
+                index as f64 / (len - 1) as f64
+            } else {
+                0.0
+            }
+        })
+    }
+}
+
+/// `u` of each point as its distance along `points` over the length of all of them.
+fn length_u(points: impl Iterator<Item = Point>) -> impl FnMut(usize, Point) -> Option<f64> {
+    let length = arc_length(points);
+    let mut distance = 0.0;
+    let mut previous = None;
+    move |_, point| {
+        if let Some(previous) = previous {
+            distance += point.distance(previous);
+        }
+        previous = Some(point);
+        Some(if length > 0.0 { distance / length } else { 0.0 })
+    }
+}
+
+/// Shades each pixel into `out`, leaving the `None` ones black.
+///
+/// The lit pixels are batch-shaded a chunk at a time through stack buffers.
+fn render_pixels<F: FromPoint, S: Shader<F>, C>(
+    shader: &S,
+    pixels: impl IntoIterator<Item = Option<(Point, Attributes)>>,
+    time: f64,
+    out: &mut [C],
+    convert: impl Fn(LinSrgb<f64>) -> C,
+) -> usize {
+    let mut frags = [F::from_point(Point::default(), time); BATCH_CHUNK];
+    let mut lit = [false; BATCH_CHUNK];
+    let mut colors = [LinSrgb::new(0.0, 0.0, 0.0); BATCH_CHUNK];
+    let mut pixels = pixels.into_iter();
+    let mut written = 0;
+
+    for out in out.chunks_mut(BATCH_CHUNK) {
+        // Gather the lit pixels so they are shaded together, then scatter their colors back.
+        let (mut len, mut num_lit) = (0, 0);
+        for (pixel, lit) in pixels.by_ref().take(out.len()).zip(&mut lit) {
+            *lit = pixel.is_some();
+            if let Some((point, attributes)) = pixel {
+                frags[num_lit] = F::from_point_with(point, time, attributes);
+                num_lit += 1;
+            }
+            len += 1;
+        }
+        shader.shade_batch_linear(&frags[..num_lit], &mut colors[..num_lit]);
+
+        let mut shaded = colors[..num_lit].iter();
+        for (out, lit) in out[..len].iter_mut().zip(lit) {
+            *out = convert(if lit {
+                *shaded.next().expect("one color per lit pixel")
+            } else {
+                LinSrgb::new(0.0, 0.0, 0.0)
+            });
+        }
+        written += len;
+        if len < out.len() {
+            break;
+        }
+    }
+    written
+}
+
+/// Shades every point of a layout at `time` into `out`.
+///
+/// Rendering stops at whichever of `points` or `out` runs out first, and the number of pixels
+/// written is returned. No allocation is performed, so this can run on the microcontroller
+/// driving the strip.
+///
+/// Fragments that carry [`Attributes`] get the index of their point. When `points` knows its
+/// exact length they also get `u`, their index over the last index, or see [`render_by_length`].
+/// They have no strip id.
+pub fn render<F: FromPoint, S: Shader<F>>(
+    shader: &S,
+    points: impl IntoIterator<Item = Point>,
+    time: f64,
+    out: &mut [LinSrgb<f64>],
+) -> usize {
+    let points = points.into_iter();
+    let u = index_u(&points);
+    render_with(shader, points, time, out, |color| color, u)
+}
+
+/// Like [`render`], but writes 8-bit sRGB encoded colors.
+pub fn render_srgb8<F: FromPoint, S: Shader<F>>(
+    shader: &S,
+    points: impl IntoIterator<Item = Point>,
+    time: f64,
+    out: &mut [Srgb<u8>],
+) -> usize {
+    let points = points.into_iter();
+    let u = index_u(&points);
+    render_with(shader, points, time, out, Srgb::from_linear, u)
+}
+
+/// Like [`render`], but `u` is the distance along the points over the length of all of them, so
+/// unevenly spaced LEDs still chase at an even speed. This walks a clone of `points` first to
+/// measure them.
+pub fn render_by_length<F: FromPoint, S: Shader<F>>(
+    shader: &S,
+    points: impl IntoIterator<Item = Point, IntoIter: Clone>,
+    time: f64,
+    out: &mut [LinSrgb<f64>],
+) -> usize {
+    let points = points.into_iter();
+    let u = length_u(points.clone());
+    render_with(shader, points, time, out, |color| color, u)
+}
+
+/// Like [`render_by_length`], but writes 8-bit sRGB encoded colors.
+pub fn render_by_length_srgb8<F: FromPoint, S: Shader<F>>(
+    shader: &S,
+    points: impl IntoIterator<Item = Point, IntoIter: Clone>,
+    time: f64,
+    out: &mut [Srgb<u8>],
+) -> usize {
+    let points = points.into_iter();
+    let u = length_u(points.clone());
+    render_with(shader, points, time, out, Srgb::from_linear, u)
+}
+
+/// Shades every LED of a [`Layout`] at `time` into `out`, by global index.
+///
+/// Dead and skipped LEDs are written as black. Fragments that carry [`Attributes`] get the
+/// index, strip and `u` of their LED from the layout.
+#[cfg(feature = "alloc")]
+pub fn render_layout<F: FromPoint, S: Shader<F>>(
+    shader: &S,
+    layout: &Layout,
+    time: f64,
+    out: &mut [LinSrgb<f64>],
+) -> usize {
+    render_pixels(shader, layout_pixels(layout), time, out, |color| color)
+}
+
+/// Like [`render_layout`], but writes 8-bit sRGB encoded colors.
+#[cfg(feature = "alloc")]
+pub fn render_layout_srgb8<F: FromPoint, S: Shader<F>>(
+    shader: &S,
+    layout: &Layout,
+    time: f64,
+    out: &mut [Srgb<u8>],
+) -> usize {
+    render_pixels(shader, layout_pixels(layout), time, out, Srgb::from_linear)
+}
+
+#[cfg(feature = "alloc")]
+fn layout_pixels(layout: &Layout) -> impl Iterator<Item = Option<(Point, Attributes)>> + '_ {
+    layout
+        .pixels()
+        .iter()
+        .map(|pixel| pixel.map(|pixel| (pixel.point, pixel.attributes)))
+}
+
+/// Models the current drawn by a strip so frames can be dimmed to fit the supply.
+///
+/// Each channel is assumed to draw current proportional to its linear intensity, which is what
+/// the PWM duty cycle of the LED controls.
+#[derive(Debug, Clone, Copy)]
+pub struct PowerBudget {
+    /// Current drawn by the red, green and blue channels of one LED at full intensity.
+    pub milliamps_per_channel: [f64; 3],
+    /// Current drawn by one LED's controller while it is dark.
+    pub idle_milliamps: f64,
+    /// The most current the whole strip may draw.
+    pub limit_milliamps: f64,
+}
+
+impl PowerBudget {
+    /// A budget using typical figures for 5V WS2812B LEDs: 20mA per channel and 1mA idle.
+    pub fn ws2812(limit_milliamps: f64) -> Self {
+        Self {
+            milliamps_per_channel: [20.0; 3],
+            idle_milliamps: 1.0,
+            limit_milliamps,
+        }
+    }
+
+    fn active_milliamps(&self, frame: &[LinSrgb<f64>]) -> f64 {
+        let [red, green, blue] = self.milliamps_per_channel;
+        frame
+            .iter()
+            .map(|color| {
+                color.red.clamp(0.0, 1.0) * red
+                    + color.green.clamp(0.0, 1.0) * green
+                    + color.blue.clamp(0.0, 1.0) * blue
+            })
+            .sum()
+    }
+
+    /// Estimated current drawn by the strip while showing `frame`.
+    pub fn milliamps(&self, frame: &[LinSrgb<f64>]) -> f64 {
+        self.idle_milliamps * frame.len() as f64 + self.active_milliamps(frame)
+    }
+
+    /// Scales `frame` down so that it fits within the budget, returning the scale that was
+    /// applied. Frames that already fit are left untouched and `1.0` is returned.
+    pub fn limit(&self, frame: &mut [LinSrgb<f64>]) -> f64 {
+        let available = self.limit_milliamps - self.idle_milliamps * frame.len() as f64;
+        let active = self.active_milliamps(frame);
+        if active <= available {
+            return 1.0;
+        }
+
+        let scale = (available / active).max(0.0);
+        for color in frame.iter_mut() {
+            *color *= scale;
+        }
+        scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use palette::{LinSrgb, Srgb};
+
+    use super::{render, render_by_length, render_by_length_srgb8, render_srgb8, PowerBudget};
+    use crate::{
+        point::{primitives::line, Point},
+        shader::{
+            primitives::{color, off},
+            FragOne, ShaderExt,
+        },
+    };
+
+    #[test]
+    fn render_line() {
+        let shader = color(LinSrgb::new(1.0, 0.0, 0.0)).checkerboard(off(), 1.0);
+        let points = line(Point::default(), Point::new(3.0, 0.0, 0.0), 4);
+
+        let mut frame = [LinSrgb::new(0.0, 0.0, 1.0); 6];
+        let written = render::<FragOne, _>(&shader, points.clone(), 0.0, &mut frame);
+        assert_eq!(written, 4);
+        assert_eq!(frame[0], LinSrgb::new(1.0, 0.0, 0.0));
+        assert_eq!(frame[1], LinSrgb::new(0.0, 0.0, 0.0));
+        assert_eq!(frame[4], LinSrgb::new(0.0, 0.0, 1.0));
+
+        let mut frame = [Srgb::new(0, 0, 0); 2];
+        let written = render_srgb8::<FragOne, _>(&shader, points, 0.0, &mut frame);
+        assert_eq!(written, 2);
+        assert_eq!(frame[0], Srgb::new(255, 0, 0));
+    }
+
+    #[test]
+    fn render_batches() {
+        use crate::shader::Shader;
+
+        struct BatchOnly;
+        impl Shader<FragOne> for BatchOnly {
+            type Output = LinSrgb<f64>;
+
+            fn shade(&self, _frag: FragOne) -> Self::Output {
+                unreachable!("rendered one fragment at a time")
+            }
+
+            fn shade_batch_linear(&self, frags: &[FragOne], out: &mut [LinSrgb<f64>]) {
+                for (frag, out) in frags.iter().zip(out) {
+                    *out = LinSrgb::new(frag.pos[0], 0.0, 0.0);
+                }
+            }
+        }
+
+        let points = line(Point::default(), Point::new(99.0, 0.0, 0.0), 100);
+        let mut frame = [LinSrgb::new(0.0, 0.0, 1.0); 120];
+        assert_eq!(render(&BatchOnly, points.clone(), 0.0, &mut frame), 100);
+        assert!(frame[..100]
+            .iter()
+            .enumerate()
+            .all(|(i, color)| (color.red - i as f64).abs() < 1e-9));
+        assert_eq!(frame[100], LinSrgb::new(0.0, 0.0, 1.0));
+
+        let mut frame = [Srgb::new(0, 0, 0); 40];
+        assert_eq!(render_srgb8(&BatchOnly, points, 0.0, &mut frame), 40);
+    }
+
+    #[test]
+    fn render_attributes() {
+        use crate::shader::{
+            field::{u, FieldExt},
+            primitives::per_strip,
+            Frag,
+        };
+
+        let white = color(LinSrgb::new(1.0, 1.0, 1.0));
+        let chase = u().mask(white);
+        let points = line(Point::default(), Point::new(0.0, 9.0, 0.0), 5);
+        let mut frame = [LinSrgb::new(0.0, 0.0, 0.0); 5];
+        render::<Frag<1>, _>(&chase, points.clone(), 0.0, &mut frame);
+        assert_eq!(frame[2], LinSrgb::new(0.5, 0.5, 0.5));
+        assert_eq!(frame[4], LinSrgb::new(1.0, 1.0, 1.0));
+
+        // `u` does not depend on the buffer size.
+        let mut short = [LinSrgb::new(0.0, 0.0, 0.0); 3];
+        render::<Frag<1>, _>(&chase, points.clone(), 0.0, &mut short);
+        assert_eq!(short[2], LinSrgb::new(0.5, 0.5, 0.5));
+        let uneven = [
+            Point::default(),
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(4.0, 0.0, 0.0),
+        ];
+        render::<Frag<1>, _>(&chase, uneven, 0.0, &mut short);
+        assert_eq!(short[1], LinSrgb::new(0.5, 0.5, 0.5));
+        render_by_length::<Frag<1>, _>(&chase, uneven, 0.0, &mut short);
+        assert_eq!(short[1], LinSrgb::new(0.25, 0.25, 0.25));
+        let mut encoded = [Srgb::new(0, 0, 0); 3];
+        render_by_length_srgb8::<Frag<1>, _>(&chase, uneven, 0.0, &mut encoded);
+        assert_eq!(encoded[2], Srgb::new(255, 255, 255));
+
+        // Points of unknown length get no `u`, and need not be walked twice.
+        let mut single_pass = uneven.into_iter().filter(|_| true).map(|point| point * 2.0);
+        render::<Frag<1>, _>(&chase, &mut single_pass, 0.0, &mut short);
+        assert_eq!(short[2], LinSrgb::new(0.0, 0.0, 0.0));
+
+        render::<Frag<1>, _>(
+            &white.by_index(|i| i % 3 == 0),
+            points.clone(),
+            0.0,
+            &mut frame,
+        );
+        assert_eq!(frame.map(|color| color.red), [1.0, 0.0, 0.0, 1.0, 0.0]);
+
+        // Plain fragments have no index, so nothing is lit.
+        render::<FragOne, _>(&white.by_index(|_| true), points.clone(), 0.0, &mut frame);
+        assert_eq!(frame[0], LinSrgb::new(0.0, 0.0, 0.0));
+
+        render::<Frag<1>, _>(
+            &per_strip([white, color(LinSrgb::new(0.0, 1.0, 0.0))]),
+            points,
+            0.0,
+            &mut frame,
+        );
+        assert_eq!(frame[1], LinSrgb::new(1.0, 1.0, 1.0));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn render_layouts() {
+        use super::render_layout;
+        use crate::{
+            point::layout::{Layout, Segment},
+            shader::{
+                field::{index, u, FieldExt},
+                primitives::{per_strip, Affine},
+                Frag, FragTwo, IntoShader,
+            },
+        };
+
+        let up = line(Point::default(), Point::new(0.0, 2.0, 0.0), 3);
+        let layout = Layout::new()
+            .with_segment(Segment::new(up.clone()).with_dead_pixels([1]))
+            .with_skipped(2)
+            .with_segment(
+                Segment::new(up)
+                    .reversed()
+                    .with_transform(Affine::translation([1.0, 0.0, 0.0])),
+            );
+        assert_eq!(layout.len(), 8);
+        assert_eq!(layout.segments(), [0..3, 5..8]);
+        assert_eq!(layout.points().count(), 5);
+
+        let dead = Layout::new().with_segment(
+            Segment::new(line(Point::default(), Point::new(4.0, 0.0, 0.0), 5))
+                .with_dead_pixels([3, 0])
+                .with_dead_pixels([3]),
+        );
+        let lit = dead.pixels().iter().map(Option::is_some);
+        assert!(lit.eq([false, true, true, false, true]));
+
+        // The reversed segment starts at the top.
+        let pixel = layout.pixels()[5].unwrap();
+        assert_eq!((pixel.point.x, pixel.point.y), (1.0, 2.0));
+        assert_eq!(pixel.attributes.u, Some(0.0));
+
+        let position = |frag: FragTwo| LinSrgb::new(frag.pos[0], frag.pos[1], 0.0);
+        let mut frame = [LinSrgb::new(0.0, 0.0, 1.0); 8];
+        assert_eq!(
+            render_layout(&position.into_shader(), &layout, 0.0, &mut frame),
+            8
+        );
+        assert_eq!(frame[1], LinSrgb::new(0.0, 0.0, 0.0));
+        assert_eq!(frame[3], LinSrgb::new(0.0, 0.0, 0.0));
+        assert_eq!(frame[7], LinSrgb::new(1.0, 0.0, 0.0));
+
+        let white = color(LinSrgb::new(1.0, 1.0, 1.0));
+        let shader = per_strip([
+            u().mask(white).boxed(),
+            index().remap((0.0, 10.0), (0.0, 1.0)).mask(white).boxed(),
+        ]);
+        render_layout::<Frag<2>, _>(&shader, &layout, 0.0, &mut frame);
+        assert_eq!(frame[2], LinSrgb::new(1.0, 1.0, 1.0));
+        assert_eq!(frame[6], LinSrgb::new(0.6, 0.6, 0.6));
+    }
+
+    #[test]
+    fn power_budget() {
+        let budget = PowerBudget::ws2812(1000.0);
+        let mut frame = [LinSrgb::new(1.0, 1.0, 1.0); 100];
+        assert_eq!(budget.milliamps(&frame), 6100.0);
+
+        let scale = budget.limit(&mut frame);
+        assert!((budget.milliamps(&frame) - 1000.0).abs() < 1e-9);
+        assert_eq!(frame[0], LinSrgb::new(scale, scale, scale));
+
+        let mut dim = [LinSrgb::new(0.1, 0.0, 0.0); 10];
+        assert_eq!(budget.limit(&mut dim), 1.0);
+    }
+
+    #[test]
+    fn color_correction() {
+        let points = line(Point::default(), Point::new(1.0, 0.0, 0.0), 2);
+        let mut frame = [LinSrgb::new(0.0, 0.0, 0.0); 2];
+
+        let shader = color(LinSrgb::new(0.25, 0.5, 1.0)).gamma(2.0);
+        render::<FragOne, _>(&shader, points.clone(), 0.0, &mut frame);
+        assert_eq!(frame[0], LinSrgb::new(0.0625, 0.25, 1.0));
+
+        let shader = color(LinSrgb::new(0.25, 0.5, 1.0)).channel_gamma([1.0, 2.0, 0.5]);
+        render::<FragOne, _>(&shader, points.clone(), 0.0, &mut frame);
+        assert_eq!(frame[0], LinSrgb::new(0.25, 0.25, 1.0));
+
+        // Bright pixels are scaled down to the cap, keeping their hue.
+        let shader = color(LinSrgb::new(1.0, 0.5, 0.0)).brightness(0.5);
+        render::<FragOne, _>(&shader, points.clone(), 0.0, &mut frame);
+        assert_eq!(frame[0], LinSrgb::new(0.5, 0.25, 0.0));
+
+        // Pixels under the cap are left alone.
+        let shader = color(LinSrgb::new(0.25, 0.5, 0.0)).brightness(0.5);
+        render::<FragOne, _>(&shader, points, 0.0, &mut frame);
+        assert_eq!(frame[0], LinSrgb::new(0.25, 0.5, 0.0));
+    }
+}
@@ -0,0 +1,232 @@
+//! A serializable tree of shader primitives that can be built into a shader at runtime.
+//!
+//! With the `serde` feature, descriptions can be stored in any format serde supports, such as JSON
+//! or RON, so patterns can be swapped by editing a config file instead of recompiling:
+//!
+//! ```json
+//! {
+//!     "type": "checkerboard",
+//!     "first": { "type": "rainbow", "selector": "position" },
+//!     "second": { "type": "off" },
+//!     "stride": 10.0
+//! }
+//! ```
+
+use alloc::boxed::Box;
+
+use palette::Srgb;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::shader::{
+    primitives::{
+        add, brightness, checkerboard, divide, gamma, mix, mod_position, mod_time, multiply, off,
+        rainbow, random_with_seed, rotate_hue, scale_position, scale_time, subtract,
+        translate_position,
+    },
+    BoxedShader, Vertex,
+};
+
+/// What a rainbow walks its hue along.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Selector {
+    Time,
+    /// The sum of every position component.
+    Position,
+    /// A single position component. Components the fragment does not have read as `0.0`.
+    Axis(usize),
+}
+
+impl Selector {
+    pub fn select<F: Vertex>(self, frag: F) -> f64 {
+        match self {
+            Selector::Time => frag.time(),
+            Selector::Position => frag.pos().iter().sum(),
+            Selector::Axis(axis) => frag.pos().get(axis).copied().unwrap_or(0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum ShaderDescription {
+    Off,
+    /// A constant sRGB color with components from 0 to 1.
+    Color {
+        color: [f64; 3],
+    },
+    Random {
+        seed: u64,
+    },
+    Rainbow {
+        selector: Selector,
+    },
+    Checkerboard {
+        first: Box<ShaderDescription>,
+        second: Box<ShaderDescription>,
+        stride: f64,
+    },
+    Mix {
+        start: Box<ShaderDescription>,
+        end: Box<ShaderDescription>,
+        factor: f64,
+    },
+    RotateHue {
+        shader: Box<ShaderDescription>,
+        angle: f64,
+    },
+    ModPosition {
+        shader: Box<ShaderDescription>,
+        modulo: f64,
+    },
+    ModTime {
+        shader: Box<ShaderDescription>,
+        modulo: f64,
+    },
+    ScalePosition {
+        shader: Box<ShaderDescription>,
+        scale: f64,
+    },
+    ScaleTime {
+        shader: Box<ShaderDescription>,
+        scale: f64,
+    },
+    TranslatePosition {
+        shader: Box<ShaderDescription>,
+        offset: f64,
+    },
+    Add {
+        left: Box<ShaderDescription>,
+        right: Box<ShaderDescription>,
+    },
+    Subtract {
+        left: Box<ShaderDescription>,
+        right: Box<ShaderDescription>,
+    },
+    Multiply {
+        left: Box<ShaderDescription>,
+        right: Box<ShaderDescription>,
+    },
+    Divide {
+        left: Box<ShaderDescription>,
+        right: Box<ShaderDescription>,
+    },
+    Gamma {
+        shader: Box<ShaderDescription>,
+        gamma: f64,
+    },
+    Brightness {
+        shader: Box<ShaderDescription>,
+        level: f64,
+    },
+}
+
+impl ShaderDescription {
+    /// Builds the described shader for fragments of type `F`.
+    pub fn build<F: Vertex + 'static>(&self) -> BoxedShader<F> {
+        match self {
+            ShaderDescription::Off => BoxedShader::new(off()),
+            ShaderDescription::Color {
+                color: [red, green, blue],
+            } => BoxedShader::new(crate::shader::primitives::color(Srgb::new(
+                *red, *green, *blue,
+            ))),
+            ShaderDescription::Random { seed } => BoxedShader::new(random_with_seed(*seed)),
+            ShaderDescription::Rainbow { selector } => {
+                let selector = *selector;
+                BoxedShader::new(rainbow(move |frag: F| selector.select(frag)))
+            }
+            ShaderDescription::Checkerboard {
+                first,
+                second,
+                stride,
+            } => BoxedShader::new(checkerboard(first.build(), second.build(), *stride)),
+            ShaderDescription::Mix { start, end, factor } => {
+                BoxedShader::new(mix(start.build(), end.build(), *factor))
+            }
+            ShaderDescription::RotateHue { shader, angle } => {
+                BoxedShader::new(rotate_hue(shader.build(), *angle))
+            }
+            ShaderDescription::ModPosition { shader, modulo } => {
+                BoxedShader::new(mod_position(shader.build(), *modulo))
+            }
+            ShaderDescription::ModTime { shader, modulo } => {
+                BoxedShader::new(mod_time(shader.build(), *modulo))
+            }
+            ShaderDescription::ScalePosition { shader, scale } => {
+                BoxedShader::new(scale_position(shader.build(), *scale))
+            }
+            ShaderDescription::ScaleTime { shader, scale } => {
+                BoxedShader::new(scale_time(shader.build(), *scale))
+            }
+            ShaderDescription::TranslatePosition { shader, offset } => {
+                BoxedShader::new(translate_position(shader.build(), *offset))
+            }
+            ShaderDescription::Add { left, right } => {
+                BoxedShader::new(add(left.build(), right.build()))
+            }
+            ShaderDescription::Subtract { left, right } => {
+                BoxedShader::new(subtract(left.build(), right.build()))
+            }
+            ShaderDescription::Multiply { left, right } => {
+                BoxedShader::new(multiply(left.build(), right.build()))
+            }
+            ShaderDescription::Divide { left, right } => {
+                BoxedShader::new(divide(left.build(), right.build()))
+            }
+            ShaderDescription::Gamma { shader, gamma: g } => {
+                BoxedShader::new(gamma(shader.build(), *g))
+            }
+            ShaderDescription::Brightness { shader, level } => {
+                BoxedShader::new(brightness(shader.build(), *level))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use palette::LinSrgb;
+
+    use super::ShaderDescription;
+    use crate::shader::{primitives::off, FragOne, Shader, ShaderExt};
+
+    #[test]
+    fn build_from_json() {
+        let description: ShaderDescription = serde_json::from_str(
+            r#"{
+                "type": "checkerboard",
+                "first": {
+                    "type": "scale_position",
+                    "shader": { "type": "rainbow", "selector": "position" },
+                    "scale": 2.0
+                },
+                "second": { "type": "off" },
+                "stride": 10.0
+            }"#,
+        )
+        .unwrap();
+
+        let shader = description.build::<FragOne>();
+        let expected = crate::shader::primitives::position_rainbow()
+            .scale_position(2.0)
+            .checkerboard(off(), 10.0);
+        for i in 0..30 {
+            let frag = FragOne {
+                pos: [i as f64],
+                time: 0.0,
+            };
+            let color: LinSrgb<f64> = expected.shade(frag);
+            assert_eq!(shader.shade(frag), color);
+        }
+
+        let json = serde_json::to_string(&description).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ShaderDescription>(&json).unwrap(),
+            description
+        );
+    }
+}
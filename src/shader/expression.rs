@@ -0,0 +1,692 @@
+//! A compact text syntax for shader descriptions that mirrors the [`ShaderExt`] method chain:
+//!
+//! ```text
+//! rainbow(pos).scale_time(2).checkerboard(off, 10)
+//! ```
+//!
+//! Every combinator can be called either as a method on its first argument or as a function, so
+//! the above is the same as `checkerboard(scale_time(rainbow(pos), 2), off, 10)`. Combinators
+//! without arguments, like `off`, may leave out the parentheses.
+//!
+//! `random` always starts from the same seed, like [`random`]; use `random_with_seed(n)` for a
+//! different sequence. Expressions may nest at most [`MAX_DEPTH`] combinators and parentheses
+//! deep, so that input typed into a dashboard cannot overflow the stack.
+//!
+//! [`ShaderExt`]: crate::shader::ShaderExt
+//! [`random`]: crate::shader::primitives::random
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::shader::{
+    description::{Selector, ShaderDescription},
+    primitives::DEFAULT_RANDOM_SEED,
+};
+
+/// How deeply combinator calls, method chains and parentheses may nest.
+pub const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Shader,
+    Number,
+    Selector,
+}
+
+impl core::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ValueKind::Shader => "a shader",
+            ValueKind::Number => "a number",
+            ValueKind::Selector => "a selector",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedCharacter(char),
+    UnexpectedToken {
+        expected: &'static str,
+    },
+    UnexpectedEnd {
+        expected: &'static str,
+    },
+    UnknownCombinator(String),
+    WrongArity {
+        combinator: String,
+        expected: usize,
+        found: usize,
+    },
+    TypeMismatch {
+        expected: ValueKind,
+        found: ValueKind,
+    },
+    /// An axis was selected that fragments of the target dimension do not have.
+    DimensionMismatch {
+        axis: usize,
+        dimensions: usize,
+    },
+    /// The expression nests deeper than [`MAX_DEPTH`].
+    TooDeep,
+    /// A seed that is not a whole number that fits in 64 bits.
+    InvalidSeed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// Byte range of the source the error refers to.
+    pub span: Range<usize>,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.kind {
+            ParseErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {c:?}")?,
+            ParseErrorKind::UnexpectedToken { expected } => write!(f, "expected {expected}")?,
+            ParseErrorKind::UnexpectedEnd { expected } => {
+                write!(f, "expected {expected}, found end of input")?
+            }
+            ParseErrorKind::UnknownCombinator(name) => write!(f, "unknown combinator `{name}`")?,
+            ParseErrorKind::WrongArity {
+                combinator,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{combinator}` takes {expected} arguments but {found} were given"
+            )?,
+            ParseErrorKind::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected}, found {found}")?
+            }
+            ParseErrorKind::DimensionMismatch { axis, dimensions } => write!(
+                f,
+                "axis {axis} does not exist on {dimensions} dimensional fragments"
+            )?,
+            ParseErrorKind::TooDeep => {
+                write!(f, "expression nests more than {MAX_DEPTH} levels deep")?
+            }
+            ParseErrorKind::InvalidSeed => {
+                f.write_str("seed must be a whole number from 0 to 2^64 - 1")?
+            }
+        }
+        write!(f, " at {}..{}", self.span.start, self.span.end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Number(f64),
+    LeftParen,
+    RightParen,
+    Comma,
+    Dot,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token<'_>, Range<usize>)>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while let Some(&c) = bytes.get(i) {
+        let start = i;
+        let token = match c {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                i += 1;
+                continue;
+            }
+            b'(' => Token::LeftParen,
+            b')' => Token::RightParen,
+            b',' => Token::Comma,
+            b'.' => Token::Dot,
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                while bytes
+                    .get(i)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_')
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Ident(&source[start..i]), start..i));
+                continue;
+            }
+            b'0'..=b'9' | b'-' => {
+                i += 1;
+                while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                    i += 1;
+                }
+                // Only treat a dot as a decimal point when digits follow, so `2.x` stays a method.
+                if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                    i += 1;
+                    while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                        i += 1;
+                    }
+                }
+                let number = source[start..i].parse().map_err(|_| ParseError {
+                    kind: ParseErrorKind::UnexpectedCharacter('-'),
+                    span: start..start + 1,
+                })?;
+                tokens.push((Token::Number(number), start..i));
+                continue;
+            }
+            _ => {
+                let c = source[start..].chars().next().unwrap();
+                return Err(ParseError {
+                    kind: ParseErrorKind::UnexpectedCharacter(c),
+                    span: start..start + c.len_utf8(),
+                });
+            }
+        };
+        i += 1;
+        tokens.push((token, start..i));
+    }
+
+    Ok(tokens)
+}
+
+enum Value {
+    Shader(ShaderDescription),
+    Number(f64),
+    Selector(Selector),
+}
+
+impl Value {
+    fn kind(&self) -> ValueKind {
+        match self {
+            Value::Shader(_) => ValueKind::Shader,
+            Value::Number(_) => ValueKind::Number,
+            Value::Selector(_) => ValueKind::Selector,
+        }
+    }
+}
+
+struct Argument {
+    value: Value,
+    span: Range<usize>,
+}
+
+impl Argument {
+    fn mismatch(&self, expected: ValueKind) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::TypeMismatch {
+                expected,
+                found: self.value.kind(),
+            },
+            span: self.span.clone(),
+        }
+    }
+
+    fn shader(self) -> Result<Box<ShaderDescription>, ParseError> {
+        match self.value {
+            Value::Shader(shader) => Ok(Box::new(shader)),
+            _ => Err(self.mismatch(ValueKind::Shader)),
+        }
+    }
+
+    fn number(self) -> Result<f64, ParseError> {
+        match self.value {
+            Value::Number(number) => Ok(number),
+            _ => Err(self.mismatch(ValueKind::Number)),
+        }
+    }
+
+    fn selector(self) -> Result<Selector, ParseError> {
+        match self.value {
+            Value::Selector(selector) => Ok(selector),
+            _ => Err(self.mismatch(ValueKind::Selector)),
+        }
+    }
+
+    fn seed(self) -> Result<u64, ParseError> {
+        let span = self.span.clone();
+        let seed = self.number()?;
+        // 2^64 is exact as a float, and every whole float below it fits in a u64.
+        if (0.0..18446744073709551616.0).contains(&seed) && seed % 1.0 == 0.0 {
+            Ok(seed as u64)
+        } else {
+            Err(ParseError {
+                kind: ParseErrorKind::InvalidSeed,
+                span,
+            })
+        }
+    }
+}
+
+fn arity(combinator: &str) -> Option<usize> {
+    Some(match combinator {
+        "off" | "random" => 0,
+        "rainbow" | "random_with_seed" => 1,
+        "color" => 3,
+        "rotate_hue" | "mod_position" | "mod_time" | "scale_position" | "scale_time"
+        | "translate_position" | "add" | "subtract" | "multiply" | "divide" | "gamma"
+        | "brightness" => 2,
+        "checkerboard" | "mix" => 3,
+        _ => return None,
+    })
+}
+
+fn combine(combinator: &str, arguments: Vec<Argument>) -> Result<ShaderDescription, ParseError> {
+    let mut arguments = arguments.into_iter();
+    let mut next = || arguments.next().unwrap();
+
+    Ok(match combinator {
+        "off" => ShaderDescription::Off,
+        "random" => ShaderDescription::Random {
+            seed: DEFAULT_RANDOM_SEED,
+        },
+        "random_with_seed" => ShaderDescription::Random {
+            seed: next().seed()?,
+        },
+        "rainbow" => ShaderDescription::Rainbow {
+            selector: next().selector()?,
+        },
+        "color" => ShaderDescription::Color {
+            color: [next().number()?, next().number()?, next().number()?],
+        },
+        "rotate_hue" => ShaderDescription::RotateHue {
+            shader: next().shader()?,
+            angle: next().number()?,
+        },
+        "mod_position" => ShaderDescription::ModPosition {
+            shader: next().shader()?,
+            modulo: next().number()?,
+        },
+        "mod_time" => ShaderDescription::ModTime {
+            shader: next().shader()?,
+            modulo: next().number()?,
+        },
+        "scale_position" => ShaderDescription::ScalePosition {
+            shader: next().shader()?,
+            scale: next().number()?,
+        },
+        "scale_time" => ShaderDescription::ScaleTime {
+            shader: next().shader()?,
+            scale: next().number()?,
+        },
+        "translate_position" => ShaderDescription::TranslatePosition {
+            shader: next().shader()?,
+            offset: next().number()?,
+        },
+        "add" => ShaderDescription::Add {
+            left: next().shader()?,
+            right: next().shader()?,
+        },
+        "subtract" => ShaderDescription::Subtract {
+            left: next().shader()?,
+            right: next().shader()?,
+        },
+        "multiply" => ShaderDescription::Multiply {
+            left: next().shader()?,
+            right: next().shader()?,
+        },
+        "divide" => ShaderDescription::Divide {
+            left: next().shader()?,
+            right: next().shader()?,
+        },
+        "gamma" => ShaderDescription::Gamma {
+            shader: next().shader()?,
+            gamma: next().number()?,
+        },
+        "brightness" => ShaderDescription::Brightness {
+            shader: next().shader()?,
+            level: next().number()?,
+        },
+        "checkerboard" => ShaderDescription::Checkerboard {
+            first: next().shader()?,
+            second: next().shader()?,
+            stride: next().number()?,
+        },
+        "mix" => ShaderDescription::Mix {
+            start: next().shader()?,
+            end: next().shader()?,
+            factor: next().number()?,
+        },
+        _ => unreachable!("combinator arity was checked before combining"),
+    })
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token<'a>, Range<usize>)>,
+    position: usize,
+    end: usize,
+    dimensions: usize,
+    /// Nesting depth of the expression being parsed, counting each call in a method chain.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).map(|(token, _)| *token)
+    }
+
+    fn next(&mut self, expected: &'static str) -> Result<(Token<'a>, Range<usize>), ParseError> {
+        let token = self.tokens.get(self.position).cloned().ok_or(ParseError {
+            kind: ParseErrorKind::UnexpectedEnd { expected },
+            span: self.end..self.end,
+        })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, token: Token<'a>, expected: &'static str) -> Result<(), ParseError> {
+        let (found, span) = self.next(expected)?;
+        if found == token {
+            Ok(())
+        } else {
+            Err(ParseError {
+                kind: ParseErrorKind::UnexpectedToken { expected },
+                span,
+            })
+        }
+    }
+
+    /// Goes one level deeper, failing at the current token past [`MAX_DEPTH`].
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth <= MAX_DEPTH {
+            return Ok(());
+        }
+        let span = match self.tokens.get(self.position) {
+            Some((_, span)) => span.clone(),
+            None => self.end..self.end,
+        };
+        Err(ParseError {
+            kind: ParseErrorKind::TooDeep,
+            span,
+        })
+    }
+
+    fn expression(&mut self) -> Result<Argument, ParseError> {
+        let depth = self.depth;
+        self.nest()?;
+        let mut receiver = self.primary()?;
+
+        while self.peek() == Some(Token::Dot) {
+            self.position += 1;
+            // Each call in the chain wraps the receiver in another shader.
+            self.nest()?;
+            let (token, name_span) = self.next("a combinator name")?;
+            let Token::Ident(name) = token else {
+                return Err(ParseError {
+                    kind: ParseErrorKind::UnexpectedToken {
+                        expected: "a combinator name",
+                    },
+                    span: name_span,
+                });
+            };
+
+            let start = receiver.span.start;
+            let mut arguments = Vec::from([receiver]);
+            let end = self.arguments(&mut arguments)?.unwrap_or(name_span.end);
+            receiver = self.call(name, name_span, arguments, start..end)?;
+        }
+
+        self.depth = depth;
+        Ok(receiver)
+    }
+
+    fn primary(&mut self) -> Result<Argument, ParseError> {
+        let (token, span) = self.next("an expression")?;
+        match token {
+            Token::Number(number) => Ok(Argument {
+                value: Value::Number(number),
+                span,
+            }),
+            Token::LeftParen => {
+                let inner = self.expression()?;
+                self.expect(Token::RightParen, "`)`")?;
+                Ok(inner)
+            }
+            Token::Ident(name) => {
+                if let Some(selector) = self.selector(name, span.clone())? {
+                    return Ok(Argument {
+                        value: Value::Selector(selector),
+                        span,
+                    });
+                }
+
+                let mut arguments = Vec::new();
+                let end = self.arguments(&mut arguments)?.unwrap_or(span.end);
+                let start = span.start;
+                self.call(name, span, arguments, start..end)
+            }
+            _ => Err(ParseError {
+                kind: ParseErrorKind::UnexpectedToken {
+                    expected: "an expression",
+                },
+                span,
+            }),
+        }
+    }
+
+    fn selector(&self, name: &str, span: Range<usize>) -> Result<Option<Selector>, ParseError> {
+        let axis = match name {
+            "time" => return Ok(Some(Selector::Time)),
+            "pos" => return Ok(Some(Selector::Position)),
+            "x" => 0,
+            "y" => 1,
+            "z" => 2,
+            _ => return Ok(None),
+        };
+
+        if axis >= self.dimensions {
+            return Err(ParseError {
+                kind: ParseErrorKind::DimensionMismatch {
+                    axis,
+                    dimensions: self.dimensions,
+                },
+                span,
+            });
+        }
+        Ok(Some(Selector::Axis(axis)))
+    }
+
+    /// Parses an optional parenthesized argument list, returning where it ends.
+    fn arguments(&mut self, arguments: &mut Vec<Argument>) -> Result<Option<usize>, ParseError> {
+        if self.peek() != Some(Token::LeftParen) {
+            return Ok(None);
+        }
+        self.position += 1;
+
+        if self.peek() != Some(Token::RightParen) {
+            loop {
+                arguments.push(self.expression()?);
+                if self.peek() == Some(Token::Comma) {
+                    self.position += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+
+        let (token, span) = self.next("`)`")?;
+        if token != Token::RightParen {
+            return Err(ParseError {
+                kind: ParseErrorKind::UnexpectedToken {
+                    expected: "`,` or `)`",
+                },
+                span,
+            });
+        }
+        Ok(Some(span.end))
+    }
+
+    fn call(
+        &self,
+        name: &str,
+        name_span: Range<usize>,
+        arguments: Vec<Argument>,
+        span: Range<usize>,
+    ) -> Result<Argument, ParseError> {
+        let expected = arity(name).ok_or_else(|| ParseError {
+            kind: ParseErrorKind::UnknownCombinator(name.into()),
+            span: name_span,
+        })?;
+        if arguments.len() != expected {
+            return Err(ParseError {
+                kind: ParseErrorKind::WrongArity {
+                    combinator: name.into(),
+                    expected,
+                    found: arguments.len(),
+                },
+                span,
+            });
+        }
+
+        Ok(Argument {
+            value: Value::Shader(combine(name, arguments)?),
+            span,
+        })
+    }
+}
+
+/// Parses a shader expression for fragments with `dimensions` position components.
+pub fn parse(source: &str, dimensions: usize) -> Result<ShaderDescription, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        end: source.len(),
+        dimensions,
+        depth: 0,
+    };
+
+    let expression = parser.expression()?;
+    if let Some((_, span)) = parser.tokens.get(parser.position) {
+        return Err(ParseError {
+            kind: ParseErrorKind::UnexpectedToken {
+                expected: "`.` or end of input",
+            },
+            span: span.clone(),
+        });
+    }
+
+    expression.shader().map(|shader| *shader)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, string::String};
+
+    use super::{parse, ParseError, ParseErrorKind, ValueKind, MAX_DEPTH};
+    use crate::shader::{
+        description::{Selector, ShaderDescription},
+        primitives::DEFAULT_RANDOM_SEED,
+    };
+
+    #[test]
+    fn method_chain() {
+        let rainbow = ShaderDescription::ScaleTime {
+            shader: Box::new(ShaderDescription::Rainbow {
+                selector: Selector::Position,
+            }),
+            scale: 2.0,
+        };
+        let expected = ShaderDescription::Checkerboard {
+            first: Box::new(rainbow),
+            second: Box::new(ShaderDescription::Off),
+            stride: 10.0,
+        };
+
+        assert_eq!(
+            parse("rainbow(pos).scale_time(2).checkerboard(off, 10)", 1),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            parse("checkerboard(scale_time(rainbow(pos), 2), off(), 10)", 1),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("rainbow(pos).sparkle(2)", 1),
+            Err(ParseError {
+                kind: ParseErrorKind::UnknownCombinator("sparkle".into()),
+                span: 13..20,
+            })
+        );
+        assert_eq!(
+            parse("off.mix(off)", 1).unwrap_err().kind,
+            ParseErrorKind::WrongArity {
+                combinator: "mix".into(),
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(
+            parse("off.checkerboard(2, off)", 1),
+            Err(ParseError {
+                kind: ParseErrorKind::TypeMismatch {
+                    expected: ValueKind::Shader,
+                    found: ValueKind::Number
+                },
+                span: 17..18,
+            })
+        );
+        assert_eq!(
+            parse("rainbow(y)", 1),
+            Err(ParseError {
+                kind: ParseErrorKind::DimensionMismatch {
+                    axis: 1,
+                    dimensions: 1
+                },
+                span: 8..9,
+            })
+        );
+        assert!(parse("rainbow(y)", 2).is_ok());
+        assert_eq!(
+            parse("off.mix(off", 1).unwrap_err().kind,
+            ParseErrorKind::UnexpectedEnd { expected: "`)`" }
+        );
+        assert_eq!(
+            parse("random_with_seed(1.5)", 1),
+            Err(ParseError {
+                kind: ParseErrorKind::InvalidSeed,
+                span: 17..20,
+            })
+        );
+        assert_eq!(
+            parse("random_with_seed(7)", 1),
+            Ok(ShaderDescription::Random { seed: 7 })
+        );
+        assert_eq!(
+            parse("random", 1),
+            Ok(ShaderDescription::Random {
+                seed: DEFAULT_RANDOM_SEED
+            })
+        );
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth: usize| "(".repeat(depth) + "off" + &")".repeat(depth);
+        assert!(parse(&nested(MAX_DEPTH - 1), 1).is_ok());
+        assert_eq!(
+            parse(&nested(MAX_DEPTH), 1),
+            Err(ParseError {
+                kind: ParseErrorKind::TooDeep,
+                span: MAX_DEPTH..MAX_DEPTH + 3,
+            })
+        );
+        assert_eq!(
+            parse(&nested(200_000), 1).unwrap_err().kind,
+            ParseErrorKind::TooDeep
+        );
+
+        let chain = |calls: usize| String::from("off") + &".rotate_hue(1)".repeat(calls);
+        assert!(parse(&chain(MAX_DEPTH / 2), 1).is_ok());
+        assert_eq!(
+            parse(&chain(200_000), 1).unwrap_err().kind,
+            ParseErrorKind::TooDeep
+        );
+
+        let calls = |depth: usize| "rotate_hue(".repeat(depth) + "off" + &", 1)".repeat(depth);
+        assert!(parse(&calls(MAX_DEPTH - 1), 1).is_ok());
+        assert_eq!(
+            parse(&calls(MAX_DEPTH), 1).unwrap_err().kind,
+            ParseErrorKind::TooDeep
+        );
+    }
+}
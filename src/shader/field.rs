@@ -0,0 +1,395 @@
+//! Scalar fields: like shaders, but giving a number per fragment instead of a color.
+//!
+//! Fields describe things like distance, noise or progress, and are combined with [`FieldExt`]
+//! before being turned into colors with [`FieldExt::rainbow`], [`FieldExt::palette`],
+//! [`FieldExt::blend`] or [`FieldExt::mask`]. Any `Fn(F) -> f64` closure is a field.
+
+use palette::{
+    num::{Round, Sqrt, Trigonometry},
+    IntoColor, LinSrgb, Mix,
+};
+
+use crate::shader::{
+    primitives::{
+        palette_gradient, rainbow, ColorStop, GradientExtend, GradientSpace, PaletteGradient,
+        Rainbow,
+    },
+    Shader, Vertex, VertexDim,
+};
+
+pub trait Field<F: Vertex>: Send + Sync {
+    fn sample(&self, frag: F) -> f64;
+}
+
+impl<F: Vertex, T: Fn(F) -> f64 + Send + Sync> Field<F> for T {
+    fn sample(&self, frag: F) -> f64 {
+        (self)(frag)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Constant(pub f64);
+impl<F: Vertex> Field<F> for Constant {
+    fn sample(&self, _frag: F) -> f64 {
+        self.0
+    }
+}
+
+pub fn constant(value: f64) -> Constant {
+    Constant(value)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Time;
+impl<F: Vertex> Field<F> for Time {
+    fn sample(&self, frag: F) -> f64 {
+        frag.time()
+    }
+}
+
+pub fn time() -> Time {
+    Time
+}
+
+/// A single component of the fragment position, or `0.0` when the fragment does not have it.
+#[derive(Debug, Clone, Copy)]
+pub struct Axis(pub usize);
+impl<F: Vertex> Field<F> for Axis {
+    fn sample(&self, frag: F) -> f64 {
+        frag.pos().get(self.0).copied().unwrap_or(0.0)
+    }
+}
+
+pub fn axis(axis: usize) -> Axis {
+    Axis(axis)
+}
+
+/// The index of the LED along the layout, or `0.0` when the fragment does not carry it.
+#[derive(Debug, Clone, Copy)]
+pub struct Index;
+impl<F: Vertex> Field<F> for Index {
+    fn sample(&self, frag: F) -> f64 {
+        frag.attributes().index.unwrap_or(0) as f64
+    }
+}
+
+pub fn index() -> Index {
+    Index
+}
+
+/// The distance along the LED's strip from 0 to 1, or `0.0` when the fragment does not carry it.
+#[derive(Debug, Clone, Copy)]
+pub struct U;
+impl<F: Vertex> Field<F> for U {
+    fn sample(&self, frag: F) -> f64 {
+        frag.attributes().u.unwrap_or(0.0)
+    }
+}
+
+pub fn u() -> U {
+    U
+}
+
+/// Euclidean distance from the fragment position to `center`.
+#[derive(Debug, Clone, Copy)]
+pub struct Distance<const D: usize> {
+    center: [f64; D],
+}
+impl<const D: usize, F: VertexDim<D>> Field<F> for Distance<D> {
+    fn sample(&self, frag: F) -> f64 {
+        let squared = frag
+            .pos_sized()
+            .iter()
+            .zip(&self.center)
+            .map(|(pos, center)| (pos - center) * (pos - center))
+            .sum::<f64>();
+        Sqrt::sqrt(squared)
+    }
+}
+
+pub fn distance<const D: usize>(center: [f64; D]) -> Distance<D> {
+    Distance { center }
+}
+
+macro_rules! binary_field_combinator {
+    ($name:ident, $func_name:ident = $op:tt) => {
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name<F: Vertex, L: Field<F>, R: Field<F>> {
+            _marker: core::marker::PhantomData<fn(F)>,
+            left: L,
+            right: R,
+        }
+        impl<F: Vertex, L: Field<F>, R: Field<F>> Field<F> for $name<F, L, R> {
+            fn sample(&self, frag: F) -> f64 {
+                self.left.sample(frag) $op self.right.sample(frag)
+            }
+        }
+
+        pub fn $func_name<F: Vertex, L: Field<F>, R: Field<F>>(left: L, right: R) -> $name<F, L, R> {
+            $name {
+                _marker: core::marker::PhantomData,
+                left,
+                right,
+            }
+        }
+    };
+}
+
+binary_field_combinator!(Sum, add = +);
+binary_field_combinator!(Difference, subtract = -);
+binary_field_combinator!(Product, multiply = *);
+binary_field_combinator!(Quotient, divide = /);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Clamp {
+        min: f64,
+        max: f64,
+    },
+    /// Hermite interpolation from 0 to 1 as the value goes from `start` to `end`.
+    Smoothstep {
+        start: f64,
+        end: f64,
+    },
+    Sin,
+    Abs,
+    /// The part after the decimal point, always positive.
+    Fract,
+    /// Linearly maps `from` onto `to`, without clamping.
+    Remap {
+        from: (f64, f64),
+        to: (f64, f64),
+    },
+}
+
+impl UnaryOp {
+    pub fn apply(self, value: f64) -> f64 {
+        match self {
+            UnaryOp::Clamp { min, max } => value.clamp(min, max),
+            UnaryOp::Smoothstep { start, end } => {
+                let t = ((value - start) / (end - start)).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }
+            UnaryOp::Sin => Trigonometry::sin(value),
+            UnaryOp::Abs => value.abs(),
+            UnaryOp::Fract => value - Round::floor(value),
+            UnaryOp::Remap { from, to } => {
+                to.0 + (value - from.0) / (from.1 - from.0) * (to.1 - to.0)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Unary<F: Vertex, X: Field<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    field: X,
+    op: UnaryOp,
+}
+impl<F: Vertex, X: Field<F>> Field<F> for Unary<F, X> {
+    fn sample(&self, frag: F) -> f64 {
+        self.op.apply(self.field.sample(frag))
+    }
+}
+
+pub fn unary<F: Vertex, X: Field<F>>(field: X, op: UnaryOp) -> Unary<F, X> {
+    Unary {
+        _marker: core::marker::PhantomData,
+        field,
+        op,
+    }
+}
+
+/// Blends from `start` to `end` by the value of a field, without the allocation of
+/// [`crate::shader::primitives::mix_by`].
+#[derive(Debug, Clone, Copy)]
+pub struct Blend<F: Vertex, X: Field<F>, S: Shader<F>, E: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    factor: X,
+    start: S,
+    end: E,
+}
+impl<F: Vertex, X: Field<F>, S: Shader<F>, E: Shader<F>> Shader<F> for Blend<F, X, S, E> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let start: LinSrgb<f64> = self.start.shade(frag).into_color();
+        start.mix(self.end.shade(frag).into_color(), self.factor.sample(frag))
+    }
+}
+
+pub fn blend<F: Vertex, X: Field<F>, S: Shader<F>, E: Shader<F>>(
+    factor: X,
+    start: S,
+    end: E,
+) -> Blend<F, X, S, E> {
+    Blend {
+        _marker: core::marker::PhantomData,
+        factor,
+        start,
+        end,
+    }
+}
+
+/// Scales a shader's brightness by a field clamped to `[0, 1]`.
+#[derive(Debug, Clone, Copy)]
+pub struct Mask<F: Vertex, X: Field<F>, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    mask: X,
+    shader: S,
+}
+impl<F: Vertex, X: Field<F>, S: Shader<F>> Shader<F> for Mask<F, X, S> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let color: LinSrgb<f64> = self.shader.shade(frag).into_color();
+        color * self.mask.sample(frag).clamp(0.0, 1.0)
+    }
+}
+
+pub fn mask<F: Vertex, X: Field<F>, S: Shader<F>>(mask: X, shader: S) -> Mask<F, X, S> {
+    Mask {
+        _marker: core::marker::PhantomData,
+        mask,
+        shader,
+    }
+}
+
+/// Methods for combining fields and turning them into shaders.
+///
+/// The arithmetic methods are prefixed with `field_` and mixing is called `blend`, so they do not
+/// clash with the [`ShaderExt`](crate::shader::ShaderExt) methods of types that are both, like
+/// [`Noise`](crate::shader::primitives::Noise).
+pub trait FieldExt<F: Vertex>: Field<F> + Sized {
+    fn field_add<R: Field<F>>(self, other: R) -> Sum<F, Self, R> {
+        add(self, other)
+    }
+
+    fn field_subtract<R: Field<F>>(self, other: R) -> Difference<F, Self, R> {
+        subtract(self, other)
+    }
+
+    fn field_multiply<R: Field<F>>(self, other: R) -> Product<F, Self, R> {
+        multiply(self, other)
+    }
+
+    fn field_divide<R: Field<F>>(self, other: R) -> Quotient<F, Self, R> {
+        divide(self, other)
+    }
+
+    fn clamp(self, min: f64, max: f64) -> Unary<F, Self> {
+        unary(self, UnaryOp::Clamp { min, max })
+    }
+
+    fn smoothstep(self, start: f64, end: f64) -> Unary<F, Self> {
+        unary(self, UnaryOp::Smoothstep { start, end })
+    }
+
+    fn sin(self) -> Unary<F, Self> {
+        unary(self, UnaryOp::Sin)
+    }
+
+    fn abs(self) -> Unary<F, Self> {
+        unary(self, UnaryOp::Abs)
+    }
+
+    fn fract(self) -> Unary<F, Self> {
+        unary(self, UnaryOp::Fract)
+    }
+
+    fn remap(self, from: (f64, f64), to: (f64, f64)) -> Unary<F, Self> {
+        unary(self, UnaryOp::Remap { from, to })
+    }
+
+    /// The field as a closure, for the primitives that take a selector or interpolator.
+    fn selector(self) -> impl Fn(F) -> f64 + Send + Sync {
+        move |frag| self.sample(frag)
+    }
+
+    fn rainbow(self) -> Rainbow<F, impl Fn(F) -> f64 + Send + Sync> {
+        rainbow(self.selector())
+    }
+
+    fn palette<P: AsRef<[ColorStop]> + AsMut<[ColorStop]>>(
+        self,
+        stops: P,
+        space: GradientSpace,
+        extend: GradientExtend,
+    ) -> PaletteGradient<F, impl Fn(F) -> f64 + Send + Sync, P> {
+        palette_gradient(self.selector(), stops, space, extend)
+    }
+
+    fn blend<S: Shader<F>, E: Shader<F>>(self, start: S, end: E) -> Blend<F, Self, S, E> {
+        blend(self, start, end)
+    }
+
+    fn mask<S: Shader<F>>(self, shader: S) -> Mask<F, Self, S> {
+        mask(self, shader)
+    }
+}
+impl<F: Vertex, T> FieldExt<F> for T where T: Field<F> {}
+
+#[cfg(test)]
+mod tests {
+    use palette::LinSrgb;
+
+    use super::{axis, constant, distance, time, Field, FieldExt};
+    use crate::shader::{
+        primitives::{color, off},
+        FragTwo, Shader,
+    };
+
+    #[test]
+    fn field_algebra() {
+        let frag = FragTwo {
+            pos: [3.0, 4.0],
+            time: 1.5,
+        };
+
+        assert_eq!(distance([0.0, 0.0]).sample(frag), 5.0);
+        assert_eq!(
+            axis(1)
+                .field_add(time())
+                .field_multiply(constant(2.0))
+                .sample(frag),
+            11.0
+        );
+        assert_eq!(time().fract().sample(frag), 0.5);
+        assert_eq!(axis(0).smoothstep(2.0, 4.0).sample(frag), 0.5);
+        assert_eq!(axis(0).remap((2.0, 4.0), (0.0, 10.0)).sample(frag), 5.0);
+        assert_eq!(
+            axis(0).field_subtract(constant(5.0)).abs().sample(frag),
+            2.0
+        );
+        assert_eq!(
+            (|frag: FragTwo| frag.time).clamp(0.0, 1.0).sample(frag),
+            1.0
+        );
+
+        let white = color(LinSrgb::new(1.0, 1.0, 1.0));
+        let mixed = time().fract().blend(off(), white);
+        assert_eq!(mixed.shade(frag), LinSrgb::new(0.5, 0.5, 0.5));
+        let masked = constant(0.25).mask(white);
+        assert_eq!(masked.shade(frag), LinSrgb::new(0.25, 0.25, 0.25));
+    }
+
+    #[test]
+    fn noise_with_both_traits() {
+        use crate::shader::{
+            primitives::{perlin_noise, simplex_noise},
+            ShaderExt,
+        };
+
+        let frag = FragTwo {
+            pos: [0.3, 0.7],
+            time: 0.0,
+        };
+        let sum = perlin_noise(1).field_add(simplex_noise(2));
+        assert_eq!(
+            sum.sample(frag),
+            perlin_noise(1).sample(frag) + simplex_noise(2).sample(frag)
+        );
+        let shader = perlin_noise(1).add(simplex_noise(2));
+        shader.shade(frag);
+    }
+}
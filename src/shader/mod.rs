@@ -1,19 +1,139 @@
+#[cfg(feature = "alloc")]
+pub mod description;
+#[cfg(feature = "alloc")]
+pub mod expression;
+pub mod field;
 pub mod primitives;
 
 use palette::{IntoColor, LinSrgb};
 #[cfg(feature = "memoize")]
 use primitives::Memoize;
 use primitives::{
-    add, checkerboard, divide, extrude, mix, mod_position, mod_time, multiply, rotate_hue,
-    scale_position, scale_time, subtract, translate_position, volume_blur, Add, Checkerboard,
-    Divide, Extrude, Interpolate, ModPosition, ModTime, Multiply, RotateHue, ScalePosition,
-    ScaleTime, Subtract, TranslatePosition, VolumeBlur,
+    add, breathe, brightness, by_index, channel_gamma, checkerboard, cylindrical,
+    cylindrical_to_cartesian, divide, embed, extrude, extrude_along, gamma, mix, mod_position,
+    mod_time, multiply, polar, polar_to_cartesian, pulse, remap_time, rotate_hue, scale_position,
+    scale_time, scroll, spherical, spherical_to_cartesian, spin, spin_about, subtract, transform,
+    transition, translate_position, volume_blur, Add, Affine, Brightness, ByIndex, Checkerboard,
+    Coordinates, Divide, Embed, Extrude, Gamma, Interpolate, ModPosition, ModTime, Multiply, Pulse,
+    RemapTime, RotateHue, ScalePosition, ScaleTime, Scroll, Spin, Subtract, Transform, Transition,
+    TransitionKind, TranslatePosition, VolumeBlur,
 };
 
 pub trait Shader<F: Vertex>: Send + Sync {
     type Output: IntoColor<LinSrgb<f64>> + Send + Sync;
 
     fn shade(&self, frag: F) -> Self::Output;
+
+    /// Shades every fragment in `frags` into the matching slot of `out`.
+    ///
+    /// Only `frags.len().min(out.len())` fragments are shaded. Shaders that can share work
+    /// between fragments override this, so prefer it over calling [`Shader::shade`] in a loop.
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        for (frag, out) in frags.iter().zip(out.iter_mut()) {
+            *out = self.shade(*frag);
+        }
+    }
+
+    /// Like [`Shader::shade_batch`], but converts every output to [`LinSrgb<f64>`].
+    ///
+    /// Combinators that blend colors batch their inner shaders through this, so shaders that
+    /// override `shade_batch` should override this too.
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        for (frag, out) in frags.iter().zip(out.iter_mut()) {
+            *out = self.shade(*frag).into_color();
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F: Vertex, S: Shader<F> + ?Sized> Shader<F> for alloc::boxed::Box<S> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        (**self).shade(frag)
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        (**self).shade_batch(frags, out)
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        (**self).shade_batch_linear(frags, out)
+    }
+}
+
+/// A shader with its concrete type erased, see [`BoxedShader`].
+#[cfg(feature = "alloc")]
+pub type DynShader<F> = dyn Shader<F, Output = LinSrgb<f64>>;
+
+#[cfg(feature = "alloc")]
+struct IntoLinear<F: Vertex, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+}
+#[cfg(feature = "alloc")]
+impl<F: Vertex, S: Shader<F>> Shader<F> for IntoLinear<F, S> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(frag).into_color()
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        self.shader.shade_batch_linear(frags, out);
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        self.shader.shade_batch_linear(frags, out);
+    }
+}
+
+/// An owned shader of any type, with its output converted to [`LinSrgb<f64>`].
+///
+/// Unlike concrete shader types, boxed shaders of different patterns can be stored together or
+/// swapped at runtime.
+#[cfg(feature = "alloc")]
+pub struct BoxedShader<F: Vertex> {
+    shader: alloc::boxed::Box<DynShader<F>>,
+}
+#[cfg(feature = "alloc")]
+impl<F: Vertex + 'static> BoxedShader<F> {
+    pub fn new<S: Shader<F> + 'static>(shader: S) -> Self {
+        Self {
+            shader: alloc::boxed::Box::new(IntoLinear {
+                _marker: core::marker::PhantomData,
+                shader,
+            }),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<F: Vertex> From<alloc::boxed::Box<DynShader<F>>> for BoxedShader<F> {
+    fn from(shader: alloc::boxed::Box<DynShader<F>>) -> Self {
+        Self { shader }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<F: Vertex> Shader<F> for BoxedShader<F> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(frag)
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        self.shader.shade_batch(frags, out)
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        self.shader.shade_batch(frags, out)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<F: Vertex> core::fmt::Debug for BoxedShader<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BoxedShader").finish_non_exhaustive()
+    }
 }
 
 pub trait IntoShader<F: Vertex, O: IntoColor<LinSrgb<f64>>> {
@@ -64,6 +184,11 @@ pub trait Vertex: Clone + Copy + core::fmt::Debug + Send + Sync {
     fn time_mut(&mut self) -> &mut f64;
     fn pos(&self) -> &[f64];
     fn pos_mut(&mut self) -> &mut [f64];
+
+    /// Metadata about the LED being shaded, which is empty unless the fragment type carries it.
+    fn attributes(&self) -> Attributes {
+        Attributes::default()
+    }
 }
 pub trait VertexDim<const D: usize>: Vertex {
     fn pos_sized(&self) -> &[f64; D];
@@ -159,6 +284,158 @@ impl Vertex for FragThree {
         &mut self.pos
     }
 }
+impl VertexDim<3> for FragThree {
+    fn pos_sized(&self) -> &[f64; 3] {
+        &self.pos
+    }
+
+    fn pos_sized_mut(&mut self) -> &mut [f64; 3] {
+        &mut self.pos
+    }
+}
+
+/// Optional per-fragment information about the LED being shaded, beyond its position.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Attributes {
+    /// Index of the LED along the whole layout.
+    pub index: Option<usize>,
+    /// Which strip or segment of the layout the LED is on.
+    pub strip: Option<usize>,
+    /// Distance along the strip, normalized so the first LED is at 0 and the last at 1.
+    pub u: Option<f64>,
+}
+
+/// A fragment of any number of dimensions, carrying [`Attributes`] alongside its position.
+///
+/// [`FragOne`], [`FragTwo`] and [`FragThree`] convert to and from `Frag<1>`, `Frag<2>` and
+/// `Frag<3>`, losing only the attributes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Frag<const D: usize> {
+    pub pos: [f64; D],
+    pub time: f64,
+    pub attributes: Attributes,
+}
+
+impl<const D: usize> Frag<D> {
+    pub fn new(pos: [f64; D], time: f64) -> Self {
+        Frag {
+            pos,
+            time,
+            attributes: Attributes::default(),
+        }
+    }
+
+    pub fn with_attributes(self, attributes: Attributes) -> Self {
+        Frag { attributes, ..self }
+    }
+
+    /// Converts to `E` dimensions by dropping the trailing axes or padding with zeros.
+    pub fn with_dimensions<const E: usize>(self) -> Frag<E> {
+        let mut pos = [0.0; E];
+        for (target, component) in pos.iter_mut().zip(self.pos) {
+            *target = component;
+        }
+        Frag {
+            pos,
+            time: self.time,
+            attributes: self.attributes,
+        }
+    }
+}
+
+impl<const D: usize> Vertex for Frag<D> {
+    fn time(&self) -> f64 {
+        self.time
+    }
+
+    fn time_mut(&mut self) -> &mut f64 {
+        &mut self.time
+    }
+
+    fn pos(&self) -> &[f64] {
+        &self.pos
+    }
+
+    fn pos_mut(&mut self) -> &mut [f64] {
+        &mut self.pos
+    }
+
+    fn attributes(&self) -> Attributes {
+        self.attributes
+    }
+}
+impl<const D: usize> VertexDim<D> for Frag<D> {
+    fn pos_sized(&self) -> &[f64; D] {
+        &self.pos
+    }
+
+    fn pos_sized_mut(&mut self) -> &mut [f64; D] {
+        &mut self.pos
+    }
+}
+
+macro_rules! generic_frag_conversion {
+    ($($frag:ident = $dimensions:literal),+) => {
+        $(
+            impl From<$frag> for Frag<$dimensions> {
+                fn from(frag: $frag) -> Self {
+                    Frag::new(frag.pos, frag.time)
+                }
+            }
+
+            impl From<Frag<$dimensions>> for $frag {
+                fn from(frag: Frag<$dimensions>) -> Self {
+                    $frag {
+                        pos: frag.pos,
+                        time: frag.time,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+generic_frag_conversion!(FragOne = 1, FragTwo = 2, FragThree = 3);
+
+// A blanket impl between any two dimensions would overlap with `From<T> for T`.
+macro_rules! generic_dimension_conversion {
+    ($($from:literal => $to:literal),+) => {
+        $(
+            impl From<Frag<$from>> for Frag<$to> {
+                fn from(frag: Frag<$from>) -> Self {
+                    frag.with_dimensions()
+                }
+            }
+        )+
+    };
+}
+
+generic_dimension_conversion!(1 => 2, 1 => 3, 2 => 1, 2 => 3, 3 => 1, 3 => 2);
+
+/// Converts between fragment dimensions by dropping the trailing axes or padding with zeros.
+macro_rules! frag_conversion {
+    ($from:ident => $($to:ident),+) => {
+        $(
+            impl From<$from> for $to {
+                fn from(frag: $from) -> Self {
+                    let mut converted = $to {
+                        pos: Default::default(),
+                        time: frag.time,
+                    };
+                    for (target, component) in converted.pos.iter_mut().zip(frag.pos) {
+                        *target = component;
+                    }
+                    converted
+                }
+            }
+        )+
+    };
+}
+
+frag_conversion!(FragOne => FragTwo, FragThree);
+frag_conversion!(FragTwo => FragOne, FragThree);
+frag_conversion!(FragThree => FragOne, FragTwo);
 
 pub trait ShaderExt<F: Vertex>: Shader<F> + Sized {
     fn mix<S: Shader<F>>(self, other: S, factor: f64) -> Interpolate<Self, S, F> {
@@ -188,7 +465,11 @@ pub trait ShaderExt<F: Vertex>: Shader<F> + Sized {
         scale_time(self, factor)
     }
 
-    fn scale_position(self, scale: f64) -> ScalePosition<F, Self> {
+    fn remap_time<E: Fn(f64) -> f64 + Send + Sync>(self, remap: E) -> RemapTime<F, Self, E> {
+        remap_time(self, remap)
+    }
+
+    fn scale_position<O>(self, scale: O) -> ScalePosition<F, Self, O> {
         scale_position(self, scale)
     }
 
@@ -196,6 +477,87 @@ pub trait ShaderExt<F: Vertex>: Shader<F> + Sized {
         translate_position(self, offset)
     }
 
+    /// Moves the pattern by `velocity` units per unit of time, e.g. `.scroll([5.0])` along a strip.
+    fn scroll<const D: usize>(self, velocity: [f64; D]) -> Scroll<D, F, Self>
+    where
+        F: VertexDim<D>,
+    {
+        scroll(self, velocity)
+    }
+
+    /// Turns the pattern counterclockwise by `rate` degrees per unit of time.
+    fn spin(self, rate: f64) -> Spin<2, F, Self>
+    where
+        F: VertexDim<2>,
+    {
+        spin(self, rate)
+    }
+
+    fn spin_about(self, axis: [f64; 3], rate: f64) -> Spin<3, F, Self>
+    where
+        F: VertexDim<3>,
+    {
+        spin_about(self, axis, rate)
+    }
+
+    /// Shades in `[radius, angle]` instead of `[x, y]`.
+    fn polar(self) -> Coordinates<2, F, Self>
+    where
+        F: VertexDim<2>,
+    {
+        polar(self)
+    }
+
+    /// Shades in `[radius, angle, z]` instead of `[x, y, z]`.
+    fn cylindrical(self) -> Coordinates<3, F, Self>
+    where
+        F: VertexDim<3>,
+    {
+        cylindrical(self)
+    }
+
+    /// Shades in `[radius, azimuth, inclination]` instead of `[x, y, z]`.
+    fn spherical(self) -> Coordinates<3, F, Self>
+    where
+        F: VertexDim<3>,
+    {
+        spherical(self)
+    }
+
+    fn polar_to_cartesian(self) -> Coordinates<2, F, Self>
+    where
+        F: VertexDim<2>,
+    {
+        polar_to_cartesian(self)
+    }
+
+    fn cylindrical_to_cartesian(self) -> Coordinates<3, F, Self>
+    where
+        F: VertexDim<3>,
+    {
+        cylindrical_to_cartesian(self)
+    }
+
+    fn spherical_to_cartesian(self) -> Coordinates<3, F, Self>
+    where
+        F: VertexDim<3>,
+    {
+        spherical_to_cartesian(self)
+    }
+
+    /// Only lights the LEDs whose index passes `predicate`.
+    fn by_index<P: Fn(usize) -> bool + Send + Sync>(self, predicate: P) -> ByIndex<F, Self, P> {
+        by_index(self, predicate)
+    }
+
+    fn pulse(self, period: f64) -> Pulse<F, Self> {
+        pulse(self, period)
+    }
+
+    fn breathe(self, period: f64) -> Pulse<F, Self> {
+        breathe(self, period)
+    }
+
     fn add<O: Shader<F>>(self, other: O) -> Add<Self, O, F> {
         add(self, other)
     }
@@ -213,6 +575,42 @@ pub trait ShaderExt<F: Vertex>: Shader<F> + Sized {
     fn volume_blur<const P: usize>(self, radius: f64) -> VolumeBlur<P, F, Self> {
         volume_blur(self, radius)
     }
+
+    fn gamma(self, exponent: f64) -> Gamma<F, Self> {
+        gamma(self, exponent)
+    }
+
+    fn channel_gamma(self, gamma: [f64; 3]) -> Gamma<F, Self> {
+        channel_gamma(self, gamma)
+    }
+
+    fn brightness(self, level: f64) -> Brightness<F, Self> {
+        brightness(self, level)
+    }
+
+    fn transition<S: Shader<F>>(
+        self,
+        other: S,
+        start: f64,
+        duration: f64,
+        kind: TransitionKind,
+    ) -> Transition<F, Self, S> {
+        transition(self, other, start, duration, kind)
+    }
+
+    /// Shades fragments of any type convertible to `F`, e.g. a 2D shader on a 1D strip.
+    fn embed(self) -> Embed<F, Self> {
+        embed(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn boxed(self) -> BoxedShader<F>
+    where
+        Self: 'static,
+        F: 'static,
+    {
+        BoxedShader::new(self)
+    }
 }
 impl<F: Vertex, T> ShaderExt<F> for T where T: Shader<F> {}
 
@@ -220,6 +618,14 @@ pub trait DimShaderExt<const D: usize, F: Vertex + VertexDim<D>>: Shader<F> + Si
     fn extrude(self) -> Extrude<D, F, Self> {
         extrude(self)
     }
+
+    fn extrude_along(self, axis: usize) -> Extrude<D, F, Self> {
+        extrude_along(self, axis)
+    }
+
+    fn transform(self, affine: Affine<D>) -> Transform<D, F, Self> {
+        transform(self, affine)
+    }
     #[cfg(feature = "memoize")]
     fn memoize(
         self,
@@ -249,6 +655,447 @@ mod tests {
         });
     }
 
+    #[test]
+    fn shade_batch_matches_shade() {
+        use crate::shader::ShaderExt;
+        use palette::LinSrgb;
+
+        let shader = crate::shader::primitives::position_rainbow()
+            .volume_blur::<4>(2.0)
+            .scale_position(0.5)
+            .mod_time(3);
+        let frags: [FragOne; 100] = core::array::from_fn(|i| FragOne {
+            pos: [i as f64],
+            time: i as f64 / 10.0,
+        });
+
+        let mut batch = [LinSrgb::new(0.0, 0.0, 0.0); 100];
+        shader.shade_batch(&frags, &mut batch);
+        for (frag, color) in frags.iter().zip(batch) {
+            assert_eq!(shader.shade(*frag), color);
+        }
+
+        let shader = crate::shader::primitives::position_rainbow()
+            .scale_time(2.0)
+            .checkerboard(crate::shader::primitives::position_rainbow(), 3.0)
+            .mix(crate::shader::primitives::position_rainbow(), 0.25);
+        shader.shade_batch(&frags, &mut batch);
+        for (frag, color) in frags.iter().zip(batch) {
+            assert_eq!(shader.shade(*frag), color);
+        }
+
+        let shader = crate::shader::primitives::position_rainbow()
+            .translate_position(1.0)
+            .mod_position(7)
+            .remap_time(|time| time * time);
+        shader.shade_batch_linear(&frags, &mut batch);
+        for (frag, color) in frags.iter().zip(batch) {
+            assert_eq!(
+                palette::IntoColor::<LinSrgb<f64>>::into_color(shader.shade(*frag)),
+                color
+            );
+        }
+    }
+
+    #[test]
+    fn palette_gradients() {
+        use crate::shader::primitives::{palette_gradient, stop, GradientExtend, GradientSpace};
+        use palette::LinSrgb;
+
+        let stops = [
+            stop(0.0, LinSrgb::new(1.0, 0.0, 0.0)),
+            stop(0.5, LinSrgb::new(0.0, 1.0, 0.0)),
+            stop(0.75, LinSrgb::new(0.0, 0.0, 1.0)),
+        ];
+        let frag = |pos| FragOne {
+            pos: [pos],
+            time: 0.0,
+        };
+
+        let clamped = palette_gradient(
+            |frag: FragOne| frag.pos[0],
+            stops,
+            GradientSpace::LinearSrgb,
+            GradientExtend::Clamp,
+        );
+        assert_eq!(clamped.shade(frag(0.25)), LinSrgb::new(0.5, 0.5, 0.0));
+        assert_eq!(clamped.shade(frag(-1.0)), LinSrgb::new(1.0, 0.0, 0.0));
+        assert_eq!(clamped.shade(frag(0.9)), LinSrgb::new(0.0, 0.0, 1.0));
+
+        let wrapped = palette_gradient(
+            |frag: FragOne| frag.pos[0],
+            stops,
+            GradientSpace::LinearSrgb,
+            GradientExtend::Wrap,
+        );
+        assert_eq!(wrapped.shade(frag(0.875)), LinSrgb::new(0.5, 0.0, 0.5));
+        assert_eq!(wrapped.shade(frag(1.25)), LinSrgb::new(0.5, 0.5, 0.0));
+
+        let okhsl = palette_gradient(
+            |frag: FragOne| frag.pos[0],
+            stops,
+            GradientSpace::Okhsl,
+            GradientExtend::Clamp,
+        );
+        let green = okhsl.shade(frag(0.5));
+        assert!((green.green - 1.0).abs() < 1e-6);
+
+        // Stops are sorted, and selectors that are not a number do not panic.
+        let [red, green, blue] = stops;
+        let unsorted = palette_gradient(
+            |frag: FragOne| frag.pos[0],
+            [blue, red, green],
+            GradientSpace::LinearSrgb,
+            GradientExtend::Clamp,
+        );
+        assert_eq!(unsorted.shade(frag(0.25)), LinSrgb::new(0.5, 0.5, 0.0));
+        assert_eq!(unsorted.shade(frag(f64::NAN)), LinSrgb::new(1.0, 0.0, 0.0));
+        let wrapped = palette_gradient(
+            |_| f64::NAN,
+            stops,
+            GradientSpace::LinearSrgb,
+            GradientExtend::Wrap,
+        );
+        assert_eq!(wrapped.shade(frag(0.0)), LinSrgb::new(1.0, 0.0, 0.0));
+        let single = palette_gradient(
+            |frag: FragOne| frag.pos[0],
+            [stop(f64::NAN, LinSrgb::new(0.0, 1.0, 0.0))],
+            GradientSpace::LinearSrgb,
+            GradientExtend::Clamp,
+        );
+        assert_eq!(single.shade(frag(0.5)), LinSrgb::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn coherent_noise() {
+        use crate::shader::primitives::{perlin_noise, simplex_noise, worley_noise};
+        use crate::shader::FragTwo;
+
+        for noise in [perlin_noise(3), simplex_noise(3), worley_noise(3)] {
+            let noise = noise.with_frequency(0.1).with_octaves(3, 2.0, 0.5);
+            let frag = |x: f64, time| FragTwo {
+                pos: [x, 2.0],
+                time,
+            };
+
+            let mut previous = noise.sample(frag(0.0, 0.0));
+            let (mut min, mut max) = (previous, previous);
+            for i in 1..2000 {
+                let value = noise.sample(frag(i as f64 * 0.05, 0.0));
+                assert!((0.0..=1.0).contains(&value));
+                // Neighboring fragments get similar values.
+                assert!((value - previous).abs() < 0.1);
+                previous = value;
+                min = min.min(value);
+                max = max.max(value);
+            }
+            assert!(max - min > 0.3);
+
+            assert_eq!(noise.sample(frag(1.5, 2.0)), noise.sample(frag(1.5, 2.0)));
+            assert_ne!(noise.sample(frag(1.5, 2.0)), noise.sample(frag(1.5, 7.0)));
+        }
+    }
+
+    #[test]
+    fn hashed_random() {
+        use crate::shader::primitives::{hashed_random, hashed_random_every, twinkle};
+
+        let frag = |pos, time| FragOne { pos: [pos], time };
+
+        let shader = hashed_random(7);
+        assert_eq!(shader.shade(frag(1.0, 0.0)), shader.shade(frag(1.0, 5.0)));
+        assert_ne!(shader.shade(frag(1.0, 0.0)), shader.shade(frag(2.0, 0.0)));
+        assert_ne!(
+            shader.shade(frag(1.0, 0.0)),
+            hashed_random(8).shade(frag(1.0, 0.0))
+        );
+
+        let shader = hashed_random_every(7, 2.0);
+        assert_eq!(shader.shade(frag(1.0, 0.5)), shader.shade(frag(1.0, 1.5)));
+        assert_ne!(shader.shade(frag(1.0, 1.5)), shader.shade(frag(1.0, 2.5)));
+
+        // Staggered pixels do not all change at the same moment.
+        let shader = twinkle(7, 2.0);
+        let changed = (0..100)
+            .filter(|&i| shader.shade(frag(i as f64, 1.9)) != shader.shade(frag(i as f64, 2.1)))
+            .count();
+        assert!(changed > 0 && changed < 50);
+    }
+
+    #[test]
+    fn transitions() {
+        use crate::shader::primitives::{color, dissolve, slide, wipe};
+        use palette::LinSrgb;
+
+        let red = color(LinSrgb::new(1.0, 0.0, 0.0));
+        let blue = color(LinSrgb::new(0.0, 0.0, 1.0));
+        let frag = |pos, time| FragOne { pos: [pos], time };
+
+        let shader = wipe(red, blue, 1.0, 2.0, 0, 0.0, 10.0);
+        assert_eq!(shader.shade(frag(4.0, 2.0)), LinSrgb::new(0.0, 0.0, 1.0));
+        assert_eq!(shader.shade(frag(6.0, 2.0)), LinSrgb::new(1.0, 0.0, 0.0));
+
+        let shader = dissolve(red, blue, 0.0, 1.0, 42);
+        let revealed = (0..1000)
+            .filter(|&i| shader.shade(frag(i as f64, 0.25)).blue == 1.0)
+            .count();
+        assert!((200..300).contains(&revealed));
+        assert_eq!(
+            shader.shade(frag(12.0, 0.25)),
+            shader.shade(frag(12.0, 0.25))
+        );
+
+        let gradient = |frag: FragOne| LinSrgb::new(frag.pos[0] / 10.0, 0.0, 0.0);
+        let shader = slide(gradient.into_shader(), blue, 0.0, 1.0, 0, 0.0, 10.0);
+        assert_eq!(shader.shade(frag(8.0, 0.5)), LinSrgb::new(0.3, 0.0, 0.0));
+        assert_eq!(shader.shade(frag(4.0, 0.5)), LinSrgb::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn transforms() {
+        use crate::shader::{
+            primitives::{rotate_2d, Affine},
+            DimShaderExt, FragThree, FragTwo, ShaderExt,
+        };
+        use palette::LinSrgb;
+
+        let position = |frag: FragTwo| LinSrgb::new(frag.pos[0], frag.pos[1], 0.0);
+        let frag = FragTwo {
+            pos: [1.0, 2.0],
+            time: 0.0,
+        };
+
+        let shader = position.into_shader().translate_position([1.0, -1.0]);
+        assert_eq!(shader.shade(frag), LinSrgb::new(2.0, 1.0, 0.0));
+        let shader = position.into_shader().scale_position([2.0, 0.5]);
+        assert_eq!(shader.shade(frag), LinSrgb::new(2.0, 1.0, 0.0));
+
+        let color = rotate_2d(position.into_shader(), 90.0).shade(frag);
+        assert!((color.red + 2.0).abs() < 1e-12 && (color.green - 1.0).abs() < 1e-12);
+
+        let affine = Affine::scale([2.0, 2.0]).then(&Affine::translation([1.0, 0.0]));
+        let shader = position.into_shader().transform(affine);
+        assert_eq!(shader.shade(frag), LinSrgb::new(3.0, 4.0, 0.0));
+
+        let rotation = Affine::<3>::rotation([0.0, 0.0, 2.0], 90.0);
+        let point = rotation.apply([1.0, 0.0, 5.0]);
+        assert!(point[0].abs() < 1e-12 && (point[1] - 1.0).abs() < 1e-12 && point[2] == 5.0);
+        let shader = (|frag: FragThree| LinSrgb::new(frag.pos[0], frag.pos[1], frag.pos[2]))
+            .into_shader()
+            .translate_position([0.0, 0.0, 1.0]);
+        let frag = FragThree {
+            pos: [0.0; 3],
+            time: 0.0,
+        };
+        assert_eq!(shader.shade(frag), LinSrgb::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn animated_transforms() {
+        use crate::shader::{primitives::color, FragTwo, ShaderExt};
+        use palette::LinSrgb;
+
+        let position = |frag: FragOne| LinSrgb::new(frag.pos[0], 0.0, 0.0);
+        let shader = position.into_shader().scroll([5.0]);
+        let frag = |pos, time| FragOne { pos: [pos], time };
+        assert_eq!(shader.shade(frag(12.0, 2.0)), LinSrgb::new(2.0, 0.0, 0.0));
+
+        // A quarter turn brings the pattern at +x around to +y.
+        let position = |frag: FragTwo| LinSrgb::new(frag.pos[0], frag.pos[1], 0.0);
+        let shader = position.into_shader().spin(90.0);
+        let spun = shader.shade(FragTwo {
+            pos: [0.0, 1.0],
+            time: 1.0,
+        });
+        assert!((spun.red - 1.0).abs() < 1e-12 && spun.green.abs() < 1e-12);
+        let frags: [FragTwo; 40] = core::array::from_fn(|i| FragTwo {
+            pos: [i as f64, 1.0],
+            time: i as f64 / 8.0,
+        });
+        let mut batch = [LinSrgb::new(0.0, 0.0, 0.0); 40];
+        shader.shade_batch(&frags, &mut batch);
+        for (frag, color) in frags.iter().zip(batch) {
+            assert_eq!(shader.shade(*frag), color);
+        }
+
+        let white = color(LinSrgb::new(1.0, 1.0, 1.0));
+        assert_eq!(
+            white.breathe(2.0).shade(frag(0.0, 1.0)),
+            white.shade(frag(0.0, 1.0))
+        );
+        assert_eq!(white.breathe(2.0).shade(frag(0.0, 4.0)).red, 0.0);
+        assert_eq!(white.pulse(2.0).shade(frag(0.0, 2.0)).red, 1.0);
+        assert_eq!(white.pulse(2.0).shade(frag(0.0, 3.0)).red, 0.25);
+    }
+
+    #[test]
+    #[should_panic(expected = "not positive")]
+    fn pulse_without_period() {
+        use crate::shader::{primitives::off, ShaderExt};
+
+        let _ = ShaderExt::<FragOne>::breathe(off(), 0.0);
+    }
+
+    #[test]
+    fn coordinate_systems() {
+        use crate::shader::{
+            primitives::{from_spherical, to_cylindrical, to_polar, to_spherical},
+            FragThree, FragTwo, ShaderExt,
+        };
+        use palette::LinSrgb;
+
+        let close = |a: [f64; 3], b: [f64; 3]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9);
+
+        let [radius, angle] = to_polar([0.0, -2.0]);
+        assert!((radius - 2.0).abs() < 1e-12 && (angle - 270.0).abs() < 1e-12);
+        assert_eq!(to_polar([1.0, -1e-20]), [1.0, 0.0]);
+        assert!(close(to_cylindrical([-1.0, 0.0, 4.0]), [1.0, 180.0, 4.0]));
+        assert!(close(to_spherical([0.0, 0.0, 3.0]), [3.0, 0.0, 0.0]));
+        let point = [1.0, -2.0, 0.5];
+        assert!(close(from_spherical(to_spherical(point)), point));
+
+        let polar = |frag: FragTwo| LinSrgb::new(frag.pos[0], frag.pos[1], 0.0);
+        let shaded = polar.into_shader().polar().shade(FragTwo {
+            pos: [0.0, 3.0],
+            time: 0.0,
+        });
+        assert!((shaded.red - 3.0).abs() < 1e-12 && (shaded.green - 90.0).abs() < 1e-12);
+
+        // Converting there and back again leaves the position unchanged.
+        let cartesian = |frag: FragThree| LinSrgb::new(frag.pos[0], frag.pos[1], frag.pos[2]);
+        let shader = cartesian
+            .into_shader()
+            .cylindrical_to_cartesian()
+            .cylindrical();
+        let shaded = shader.shade(FragThree {
+            pos: point,
+            time: 0.0,
+        });
+        assert!(close([shaded.red, shaded.green, shaded.blue], point));
+    }
+
+    #[test]
+    fn dimension_conversions() {
+        use crate::shader::{DimShaderExt, FragThree, FragTwo, ShaderExt};
+        use palette::LinSrgb;
+
+        let frag = FragThree {
+            pos: [1.0, 2.0, 3.0],
+            time: 4.0,
+        };
+        assert_eq!(
+            FragOne::from(frag),
+            FragOne {
+                pos: [1.0],
+                time: 4.0
+            }
+        );
+        assert_eq!(FragThree::from(FragTwo::from(frag)).pos, [1.0, 2.0, 0.0]);
+
+        let flat = |frag: FragTwo| LinSrgb::new(frag.pos[0], frag.pos[1], 0.0);
+        let shader = flat.into_shader().extrude();
+        assert_eq!(shader.shade(frag), LinSrgb::new(1.0, 2.0, 0.0));
+        let shader = flat.into_shader().extrude_along(0);
+        assert_eq!(shader.shade(frag), LinSrgb::new(2.0, 3.0, 0.0));
+
+        let strip = FragOne {
+            pos: [5.0],
+            time: 0.0,
+        };
+        assert_eq!(
+            flat.into_shader().embed().shade(strip),
+            LinSrgb::new(5.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn generic_frags() {
+        use crate::shader::{Attributes, DimShaderExt, Frag, FragThree, ShaderExt};
+        use palette::LinSrgb;
+
+        let frag = FragThree {
+            pos: [1.0, 2.0, 3.0],
+            time: 4.0,
+        };
+        let generic = Frag::from(frag);
+        assert_eq!(generic, Frag::new([1.0, 2.0, 3.0], 4.0));
+        assert_eq!(FragThree::from(generic).pos, frag.pos);
+        assert_eq!(generic.with_dimensions::<1>().pos, [1.0]);
+        assert_eq!(generic.with_dimensions::<4>().pos, [1.0, 2.0, 3.0, 0.0]);
+
+        let attributes = Attributes {
+            index: Some(7),
+            ..Default::default()
+        };
+        let shader = (|frag: Frag<2>| LinSrgb::new(frag.pos[0], frag.pos[1], 0.0))
+            .into_shader()
+            .translate_position([1.0, 1.0])
+            .extrude_along(1);
+        let frag = Frag::new([2.0, 5.0, 3.0], 0.0).with_attributes(attributes);
+        assert_eq!(Frag::<1>::from(frag).attributes, attributes);
+        assert_eq!(shader.shade(frag), LinSrgb::new(3.0, 4.0, 0.0));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn boxed_shaders() {
+        use crate::shader::{
+            primitives::{color, off, position_rainbow},
+            BoxedShader, ShaderExt,
+        };
+        use alloc::vec::Vec;
+        use palette::LinSrgb;
+
+        let shaders: Vec<BoxedShader<FragOne>> = Vec::from([
+            off().boxed(),
+            color(Srgb::new(1.0, 0.0, 0.0)).boxed(),
+            position_rainbow().scale_position(2.0).boxed(),
+        ]);
+        let frag = FragOne {
+            pos: [3.0],
+            time: 0.0,
+        };
+        assert_eq!(shaders[1].shade(frag), LinSrgb::new(1.0, 0.0, 0.0));
+
+        let combined = shaders
+            .into_iter()
+            .reduce(|a, b| a.add(b).boxed())
+            .unwrap()
+            .checkerboard(off(), 10.0);
+        combined.shade(frag);
+
+        // Batches go through to the boxed shader instead of being shaded one by one.
+        static SHADES: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+        struct Counting;
+        impl Shader<FragOne> for Counting {
+            type Output = Srgb<f64>;
+
+            fn shade(&self, _frag: FragOne) -> Self::Output {
+                SHADES.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+                Srgb::new(1.0, 1.0, 1.0)
+            }
+
+            fn shade_batch_linear(&self, frags: &[FragOne], out: &mut [LinSrgb<f64>]) {
+                let len = frags.len().min(out.len());
+                out[..len].fill(LinSrgb::new(1.0, 1.0, 1.0));
+            }
+        }
+        let mut out = [LinSrgb::new(0.0, 0.0, 0.0); 100];
+        BoxedShader::new(Counting).shade_batch(&[frag; 100], &mut out);
+        assert!(out
+            .iter()
+            .all(|color| *color == LinSrgb::new(1.0, 1.0, 1.0)));
+        assert_eq!(SHADES.load(core::sync::atomic::Ordering::Relaxed), 0);
+        let shader = BoxedShader::new(Counting)
+            .scale_position(2.0)
+            .mix(off(), 0.5)
+            .boxed();
+        shader.shade_batch(&[frag; 100], &mut out);
+        assert_eq!(SHADES.load(core::sync::atomic::Ordering::Relaxed), 0);
+        shader.shade(frag);
+        assert_eq!(SHADES.load(core::sync::atomic::Ordering::Relaxed), 1);
+    }
+
     #[cfg(feature = "std")]
     #[bench]
     fn bench_rainbow_shader(b: &mut test::Bencher) {
@@ -292,6 +1139,19 @@ mod tests {
         });
     }
 
+    #[cfg(feature = "std")]
+    #[bench]
+    fn bench_volume_blur_batch(b: &mut test::Bencher) {
+        use crate::shader::ShaderExt;
+        let shader = crate::shader::primitives::position_rainbow().volume_blur::<12>(10.0);
+        let frags: [FragOne; 600] = core::array::from_fn(|i| FragOne {
+            pos: [i as f64],
+            time: 0.0,
+        });
+        let mut out = [palette::LinSrgb::new(0.0, 0.0, 0.0); 600];
+        b.iter(|| shader.shade_batch(&frags, &mut out));
+    }
+
     #[cfg(feature = "memoize")]
     #[bench]
     fn bench_volume_blur_memoized(b: &mut test::Bencher) {
@@ -0,0 +1,187 @@
+use core::f64::consts::TAU;
+
+use palette::{
+    num::{Round, Trigonometry},
+    IntoColor, LinSrgb,
+};
+
+use crate::shader::{Shader, Vertex, VertexDim};
+
+use super::{
+    operation::{shade_batch_linear_mapped, shade_batch_mapped},
+    Affine,
+};
+
+/// Moves the pattern by `velocity` units per unit of time.
+#[derive(Debug, Clone, Copy)]
+pub struct Scroll<const D: usize, F: Vertex, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    velocity: [f64; D],
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Scroll<D, F, S> {
+    fn map(&self, mut frag: F) -> F {
+        let time = frag.time();
+        for (component, velocity) in frag.pos_sized_mut().iter_mut().zip(self.velocity) {
+            *component -= velocity * time;
+        }
+        frag
+    }
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Shader<F> for Scroll<D, F, S> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+pub fn scroll<const D: usize, F: VertexDim<D>, S: Shader<F>>(
+    shader: S,
+    velocity: [f64; D],
+) -> Scroll<D, F, S> {
+    Scroll {
+        _marker: core::marker::PhantomData,
+        shader,
+        velocity,
+    }
+}
+
+/// Turns the pattern counterclockwise about the origin by `rate` degrees per unit of time.
+///
+/// In three dimensions the pattern turns about `axis`, which is ignored in two.
+#[derive(Debug, Clone, Copy)]
+pub struct Spin<const D: usize, F: Vertex, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    axis: [f64; 3],
+    rate: f64,
+}
+
+impl<F: VertexDim<2>, S: Shader<F>> Spin<2, F, S> {
+    fn map(&self, mut frag: F) -> F {
+        // Turning the fragments backwards turns the pattern forwards.
+        let rotation = Affine::<2>::rotation(-self.rate * frag.time());
+        *frag.pos_sized_mut() = rotation.apply(*frag.pos_sized());
+        frag
+    }
+}
+
+impl<F: VertexDim<2>, S: Shader<F>> Shader<F> for Spin<2, F, S> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+impl<F: VertexDim<3>, S: Shader<F>> Spin<3, F, S> {
+    fn map(&self, mut frag: F) -> F {
+        let rotation = Affine::<3>::rotation(self.axis, -self.rate * frag.time());
+        *frag.pos_sized_mut() = rotation.apply(*frag.pos_sized());
+        frag
+    }
+}
+
+impl<F: VertexDim<3>, S: Shader<F>> Shader<F> for Spin<3, F, S> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+pub fn spin<F: VertexDim<2>, S: Shader<F>>(shader: S, rate: f64) -> Spin<2, F, S> {
+    Spin {
+        _marker: core::marker::PhantomData,
+        shader,
+        axis: [0.0, 0.0, 1.0],
+        rate,
+    }
+}
+
+pub fn spin_about<F: VertexDim<3>, S: Shader<F>>(
+    shader: S,
+    axis: [f64; 3],
+    rate: f64,
+) -> Spin<3, F, S> {
+    Spin {
+        _marker: core::marker::PhantomData,
+        shader,
+        axis,
+        rate,
+    }
+}
+
+/// Scales the brightness of a shader by a repeating envelope of the fragment time.
+#[derive(Debug, Clone, Copy)]
+pub struct Pulse<F: Vertex, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    period: f64,
+    envelope: fn(f64) -> f64,
+}
+
+impl<F: Vertex, S: Shader<F>> Shader<F> for Pulse<F, S> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let phase = frag.time() / self.period;
+        let color: LinSrgb<f64> = self.shader.shade(frag).into_color();
+        color * (self.envelope)(phase - Round::floor(phase))
+    }
+}
+
+/// Flashes to full brightness at the start of every `period` and fades out until the next one.
+///
+/// # Panics
+/// If `period` is not positive.
+pub fn pulse<F: Vertex, S: Shader<F>>(shader: S, period: f64) -> Pulse<F, S> {
+    assert!(period > 0.0, "Pulse period {period} is not positive.");
+    Pulse {
+        _marker: core::marker::PhantomData,
+        shader,
+        period,
+        envelope: |phase| (1.0 - phase) * (1.0 - phase),
+    }
+}
+
+/// Smoothly fades in and back out over every `period`, starting dark.
+///
+/// # Panics
+/// If `period` is not positive.
+pub fn breathe<F: Vertex, S: Shader<F>>(shader: S, period: f64) -> Pulse<F, S> {
+    assert!(period > 0.0, "Pulse period {period} is not positive.");
+    Pulse {
+        _marker: core::marker::PhantomData,
+        shader,
+        period,
+        envelope: |phase| 0.5 - Trigonometry::cos(phase * TAU) / 2.0,
+    }
+}
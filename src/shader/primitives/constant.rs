@@ -11,6 +11,15 @@ impl<F: Vertex> Shader<F> for Off {
         // Full black
         LinSrgb::new(0.0, 0.0, 0.0)
     }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        let len = frags.len().min(out.len());
+        out[..len].fill(LinSrgb::new(0.0, 0.0, 0.0));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        self.shade_batch(frags, out);
+    }
 }
 
 pub fn off() -> Off {
@@ -28,6 +37,15 @@ impl<F: Vertex> Shader<F> for Color {
     fn shade(&self, _frag: F) -> Self::Output {
         self.color
     }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        let len = frags.len().min(out.len());
+        out[..len].fill(self.color);
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        self.shade_batch(frags, out);
+    }
 }
 
 pub fn color(color: impl IntoColor<LinSrgb<f64>>) -> Color {
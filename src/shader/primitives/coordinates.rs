@@ -0,0 +1,131 @@
+//! Conversions between Cartesian positions and polar, cylindrical or spherical coordinates.
+//!
+//! Angles are in degrees, with azimuths counterclockwise from the +x axis in `[0, 360)` so that a
+//! [`rainbow`](super::rainbow) over the angle wraps seamlessly around a ring.
+
+use palette::{
+    num::{Hypot, Sqrt, Trigonometry},
+    LinSrgb,
+};
+
+use crate::shader::{Shader, VertexDim};
+
+use super::operation::{shade_batch_linear_mapped, shade_batch_mapped};
+
+/// `[x, y]` to `[radius, angle]`.
+pub fn to_polar([x, y]: [f64; 2]) -> [f64; 2] {
+    let angle = Trigonometry::atan2(y, x).to_degrees();
+    let angle = if angle < 0.0 { angle + 360.0 } else { angle };
+    // Tiny negative angles round up to exactly 360.
+    [Hypot::hypot(x, y), if angle >= 360.0 { 0.0 } else { angle }]
+}
+
+/// `[radius, angle]` to `[x, y]`.
+pub fn from_polar([radius, angle]: [f64; 2]) -> [f64; 2] {
+    let (sin, cos) = Trigonometry::sin_cos(angle.to_radians());
+    [radius * cos, radius * sin]
+}
+
+/// `[x, y, z]` to `[radius, angle, z]`, measuring the radius from the z axis.
+pub fn to_cylindrical([x, y, z]: [f64; 3]) -> [f64; 3] {
+    let [radius, angle] = to_polar([x, y]);
+    [radius, angle, z]
+}
+
+/// `[radius, angle, z]` to `[x, y, z]`.
+pub fn from_cylindrical([radius, angle, z]: [f64; 3]) -> [f64; 3] {
+    let [x, y] = from_polar([radius, angle]);
+    [x, y, z]
+}
+
+/// `[x, y, z]` to `[radius, azimuth, inclination]`, with the inclination measured down from the
+/// +z axis in `[0, 180]`.
+pub fn to_spherical([x, y, z]: [f64; 3]) -> [f64; 3] {
+    let radius = Sqrt::sqrt(x * x + y * y + z * z);
+    let [_, azimuth] = to_polar([x, y]);
+    let inclination = if radius == 0.0 {
+        0.0
+    } else {
+        Trigonometry::acos((z / radius).clamp(-1.0, 1.0)).to_degrees()
+    };
+    [radius, azimuth, inclination]
+}
+
+/// `[radius, azimuth, inclination]` to `[x, y, z]`.
+pub fn from_spherical([radius, azimuth, inclination]: [f64; 3]) -> [f64; 3] {
+    let (sin, cos) = Trigonometry::sin_cos(inclination.to_radians());
+    let [x, y] = from_polar([radius * sin, azimuth]);
+    [x, y, radius * cos]
+}
+
+/// Converts the fragment position to another coordinate system before shading.
+#[derive(Debug, Clone, Copy)]
+pub struct Coordinates<const D: usize, F: VertexDim<D>, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    convert: fn([f64; D]) -> [f64; D],
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Coordinates<D, F, S> {
+    fn map(&self, mut frag: F) -> F {
+        *frag.pos_sized_mut() = (self.convert)(*frag.pos_sized());
+        frag
+    }
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Shader<F> for Coordinates<D, F, S> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+fn coordinates<const D: usize, F: VertexDim<D>, S: Shader<F>>(
+    shader: S,
+    convert: fn([f64; D]) -> [f64; D],
+) -> Coordinates<D, F, S> {
+    Coordinates {
+        _marker: core::marker::PhantomData,
+        shader,
+        convert,
+    }
+}
+
+/// Shades in `[radius, angle]` instead of `[x, y]`.
+pub fn polar<F: VertexDim<2>, S: Shader<F>>(shader: S) -> Coordinates<2, F, S> {
+    coordinates(shader, to_polar)
+}
+
+/// Shades in `[radius, angle, z]` instead of `[x, y, z]`.
+pub fn cylindrical<F: VertexDim<3>, S: Shader<F>>(shader: S) -> Coordinates<3, F, S> {
+    coordinates(shader, to_cylindrical)
+}
+
+/// Shades in `[radius, azimuth, inclination]` instead of `[x, y, z]`.
+pub fn spherical<F: VertexDim<3>, S: Shader<F>>(shader: S) -> Coordinates<3, F, S> {
+    coordinates(shader, to_spherical)
+}
+
+/// Shades fragments positioned in `[radius, angle]` with a Cartesian shader.
+pub fn polar_to_cartesian<F: VertexDim<2>, S: Shader<F>>(shader: S) -> Coordinates<2, F, S> {
+    coordinates(shader, from_polar)
+}
+
+/// Shades fragments positioned in `[radius, angle, z]` with a Cartesian shader.
+pub fn cylindrical_to_cartesian<F: VertexDim<3>, S: Shader<F>>(shader: S) -> Coordinates<3, F, S> {
+    coordinates(shader, from_cylindrical)
+}
+
+/// Shades fragments positioned in `[radius, azimuth, inclination]` with a Cartesian shader.
+pub fn spherical_to_cartesian<F: VertexDim<3>, S: Shader<F>>(shader: S) -> Coordinates<3, F, S> {
+    coordinates(shader, from_spherical)
+}
@@ -0,0 +1,67 @@
+use palette::{num::Powf, IntoColor, LinSrgb};
+
+use crate::shader::{Shader, Vertex};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Gamma<F: Vertex, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    gamma: [f64; 3],
+}
+impl<F: Vertex, S: Shader<F>> Shader<F> for Gamma<F, S> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let color: LinSrgb<f64> = self.shader.shade(frag).into_color();
+        let [red, green, blue] = self.gamma;
+        LinSrgb::new(
+            Powf::powf(color.red.max(0.0), red),
+            Powf::powf(color.green.max(0.0), green),
+            Powf::powf(color.blue.max(0.0), blue),
+        )
+    }
+}
+
+/// Raises every channel to the power of `gamma`.
+pub fn gamma<F: Vertex, S: Shader<F>>(shader: S, gamma: f64) -> Gamma<F, S> {
+    channel_gamma(shader, [gamma; 3])
+}
+
+/// Like [`gamma`], with a separate exponent for the red, green and blue channels.
+pub fn channel_gamma<F: Vertex, S: Shader<F>>(shader: S, gamma: [f64; 3]) -> Gamma<F, S> {
+    Gamma {
+        _marker: core::marker::PhantomData,
+        shader,
+        gamma,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Brightness<F: Vertex, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    level: f64,
+}
+impl<F: Vertex, S: Shader<F>> Shader<F> for Brightness<F, S> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let color: LinSrgb<f64> = self.shader.shade(frag).into_color();
+        let max = color.red.max(color.green).max(color.blue);
+        if max > self.level {
+            color * (self.level / max)
+        } else {
+            color
+        }
+    }
+}
+
+/// Caps every pixel at `level` of full brightness, scaling down the ones with a brighter channel
+/// so they keep their hue. Pixels that are already dimmer are left unchanged.
+pub fn brightness<F: Vertex, S: Shader<F>>(shader: S, level: f64) -> Brightness<F, S> {
+    Brightness {
+        _marker: core::marker::PhantomData,
+        shader,
+        level: level.clamp(0.0, 1.0),
+    }
+}
@@ -0,0 +1,23 @@
+//! Stateless hashing of fragment positions, for primitives that need per-pixel randomness that
+//! stays the same from frame to frame.
+
+/// The SplitMix64 finalizer, which spreads every input bit over the whole output.
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58476d1ce4e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+pub(crate) fn hash_position(seed: u64, pos: &[f64]) -> u64 {
+    // Adding zero turns -0.0 into 0.0 so both hash the same.
+    pos.iter().fold(mix(seed), |hash, component| {
+        mix(hash ^ (component + 0.0).to_bits())
+    })
+}
+
+/// Maps a hash to a float in `[0, 1)`.
+pub(crate) fn unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,62 @@
+use palette::{IntoColor, LinSrgb};
+
+use crate::shader::{Shader, Vertex};
+
+/// Only lights the LEDs whose index passes `predicate`, leaving the rest and any fragments
+/// without an index off.
+#[derive(Debug, Clone, Copy)]
+pub struct ByIndex<F: Vertex, S: Shader<F>, P: Fn(usize) -> bool + Send + Sync> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    predicate: P,
+}
+
+impl<F: Vertex, S: Shader<F>, P: Fn(usize) -> bool + Send + Sync> Shader<F> for ByIndex<F, S, P> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        match frag.attributes().index {
+            Some(index) if (self.predicate)(index) => self.shader.shade(frag).into_color(),
+            _ => LinSrgb::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Lights the LEDs whose index passes `predicate`, e.g. `|index| index % 3 == 0` for every third.
+pub fn by_index<F: Vertex, S: Shader<F>, P: Fn(usize) -> bool + Send + Sync>(
+    shader: S,
+    predicate: P,
+) -> ByIndex<F, S, P> {
+    ByIndex {
+        _marker: core::marker::PhantomData,
+        shader,
+        predicate,
+    }
+}
+
+/// Shades each strip of a layout with its own shader.
+#[derive(Debug, Clone, Copy)]
+pub struct PerStrip<F: Vertex, S: Shader<F>, const N: usize> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shaders: [S; N],
+}
+
+impl<F: Vertex, S: Shader<F>, const N: usize> Shader<F> for PerStrip<F, S, N> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        match self.shaders.get(frag.attributes().strip.unwrap_or(0)) {
+            Some(shader) => shader.shade(frag).into_color(),
+            None => LinSrgb::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Shades strip `i` with `shaders[i]`, leaving strips past the end off. Fragments without a strip
+/// id are treated as strip 0.
+pub fn per_strip<F: Vertex, S: Shader<F>, const N: usize>(shaders: [S; N]) -> PerStrip<F, S, N> {
+    PerStrip {
+        _marker: core::marker::PhantomData,
+        shaders,
+    }
+}
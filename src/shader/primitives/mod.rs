@@ -3,10 +3,27 @@ mod memoize;
 #[cfg(feature = "memoize")]
 pub use memoize::*;
 
+mod animate;
 mod constant;
+mod coordinates;
+mod correction;
+mod hash;
+mod metadata;
+mod noise;
 mod operation;
 mod pattern;
+mod select;
+mod transform;
+mod transition;
 
+pub use animate::*;
 pub use constant::*;
+pub use coordinates::*;
+pub use correction::*;
+pub use metadata::*;
+pub use noise::*;
 pub use operation::*;
 pub use pattern::*;
+pub use select::*;
+pub use transform::*;
+pub use transition::*;
@@ -0,0 +1,303 @@
+use palette::{
+    num::{Round, Sqrt},
+    LinSrgb,
+};
+
+use crate::shader::{field::Field, Shader, Vertex};
+
+use super::hash::{hash_position, mix, unit};
+
+/// Up to three position components plus time.
+const MAX_DIMENSIONS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    /// Classic gradient noise on a square grid.
+    Perlin,
+    /// Gradient noise on a simplex grid, with fewer directional artifacts than Perlin noise.
+    Simplex,
+    /// Cellular noise: the distance to the nearest of a set of randomly scattered points.
+    Worley,
+}
+
+/// Deterministic coherent noise over the fragment position, with time as an extra dimension.
+///
+/// The same fragment always gives the same value, so frames can be rendered from any number of
+/// threads. Used as a shader the noise is shaded as gray, but it is more often used as a
+/// [`Field`] to drive another shader, such as the selector of a
+/// [`palette_gradient`](super::palette_gradient).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+    pub kind: NoiseKind,
+    pub seed: u64,
+    /// Scale applied to the fragment position. Larger values give smaller features.
+    pub frequency: f64,
+    /// Scale applied to the fragment time. Zero gives noise that does not change over time.
+    pub time_frequency: f64,
+    /// Number of layers of fractal Brownian motion. Each layer adds finer detail.
+    pub octaves: u32,
+    /// How much the frequency grows between octaves.
+    pub lacunarity: f64,
+    /// How much the amplitude shrinks between octaves.
+    pub gain: f64,
+}
+
+impl Noise {
+    pub fn new(kind: NoiseKind, seed: u64) -> Self {
+        Noise {
+            kind,
+            seed,
+            frequency: 1.0,
+            time_frequency: 1.0,
+            octaves: 1,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    pub fn with_frequency(self, frequency: f64) -> Self {
+        Noise { frequency, ..self }
+    }
+
+    pub fn with_time_frequency(self, time_frequency: f64) -> Self {
+        Noise {
+            time_frequency,
+            ..self
+        }
+    }
+
+    /// Layers `octaves` copies of the noise, each at `lacunarity` times the frequency and `gain`
+    /// times the amplitude of the one before.
+    pub fn with_octaves(self, octaves: u32, lacunarity: f64, gain: f64) -> Self {
+        Noise {
+            octaves,
+            lacunarity,
+            gain,
+            ..self
+        }
+    }
+
+    /// Samples the noise at a fragment, giving a value in `[0, 1]`.
+    pub fn sample<F: Vertex>(&self, frag: F) -> f64 {
+        let mut point = [0.0; MAX_DIMENSIONS];
+        let pos = frag.pos();
+        let mut dimensions = pos.len().min(MAX_DIMENSIONS - 1);
+        for (component, pos) in point.iter_mut().zip(&pos[..dimensions]) {
+            *component = pos * self.frequency;
+        }
+        if self.time_frequency != 0.0 {
+            point[dimensions] = frag.time() * self.time_frequency;
+            dimensions += 1;
+        }
+
+        self.sample_point(&point[..dimensions])
+    }
+
+    /// Samples the noise at an arbitrary point of up to four dimensions, ignoring the frequency.
+    pub fn sample_point(&self, point: &[f64]) -> f64 {
+        let mut scaled = [0.0; MAX_DIMENSIONS];
+        let dimensions = point.len().min(MAX_DIMENSIONS);
+        scaled[..dimensions].copy_from_slice(&point[..dimensions]);
+
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut amplitudes = 0.0;
+        for octave in 0..self.octaves.max(1) {
+            let seed = mix(self.seed.wrapping_add(octave as u64));
+            let value = match self.kind {
+                NoiseKind::Perlin => perlin(seed, &scaled[..dimensions]) * 0.5 + 0.5,
+                NoiseKind::Simplex => simplex(seed, &scaled[..dimensions]) * 0.5 + 0.5,
+                NoiseKind::Worley => worley(seed, &scaled[..dimensions]),
+            };
+            total += value * amplitude;
+            amplitudes += amplitude;
+
+            amplitude *= self.gain;
+            for component in &mut scaled[..dimensions] {
+                *component *= self.lacunarity;
+            }
+        }
+
+        (total / amplitudes).clamp(0.0, 1.0)
+    }
+
+    /// The noise as a scalar field, for use as a selector or interpolator.
+    pub fn field<F: Vertex>(self) -> impl Fn(F) -> f64 + Copy + Send + Sync {
+        move |frag| self.sample(frag)
+    }
+}
+
+impl<F: Vertex> Field<F> for Noise {
+    fn sample(&self, frag: F) -> f64 {
+        Noise::sample(self, frag)
+    }
+}
+
+impl<F: Vertex> Shader<F> for Noise {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let value = self.sample(frag);
+        LinSrgb::new(value, value, value)
+    }
+}
+
+pub fn perlin_noise(seed: u64) -> Noise {
+    Noise::new(NoiseKind::Perlin, seed)
+}
+
+pub fn simplex_noise(seed: u64) -> Noise {
+    Noise::new(NoiseKind::Simplex, seed)
+}
+
+pub fn worley_noise(seed: u64) -> Noise {
+    Noise::new(NoiseKind::Worley, seed)
+}
+
+/// A pseudo-random unit vector for a lattice point.
+fn gradient(seed: u64, lattice: &[f64]) -> [f64; MAX_DIMENSIONS] {
+    let mut hash = hash_position(seed, lattice);
+    let mut gradient = [0.0; MAX_DIMENSIONS];
+    let mut length = 0.0;
+    for component in &mut gradient[..lattice.len()] {
+        hash = mix(hash);
+        *component = unit(hash) * 2.0 - 1.0;
+        length += *component * *component;
+    }
+
+    let length = Sqrt::sqrt(length);
+    if length > 0.0 {
+        for component in &mut gradient {
+            *component /= length;
+        }
+    }
+    gradient
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// Gradient noise in `[-1, 1]`.
+fn perlin(seed: u64, point: &[f64]) -> f64 {
+    let n = point.len();
+    if n == 0 {
+        return 0.0;
+    }
+    let mut cell = [0.0; MAX_DIMENSIONS];
+    let mut offset = [0.0; MAX_DIMENSIONS];
+    for i in 0..n {
+        cell[i] = Round::floor(point[i]);
+        offset[i] = point[i] - cell[i];
+    }
+
+    let mut total = 0.0;
+    for corner in 0..1usize << n {
+        let mut lattice = cell;
+        let mut delta = offset;
+        let mut weight = 1.0;
+        for i in 0..n {
+            let fade = fade(offset[i]);
+            if corner & (1 << i) != 0 {
+                lattice[i] += 1.0;
+                delta[i] -= 1.0;
+                weight *= fade;
+            } else {
+                weight *= 1.0 - fade;
+            }
+        }
+
+        let gradient = gradient(seed, &lattice[..n]);
+        total += weight * dot(&gradient[..n], &delta[..n]);
+    }
+
+    (total * PERLIN_SCALE[n - 1]).clamp(-1.0, 1.0)
+}
+
+/// Simplex noise in `[-1, 1]`.
+fn simplex(seed: u64, point: &[f64]) -> f64 {
+    let n = point.len();
+    if n == 0 {
+        return 0.0;
+    }
+    let dimensions = n as f64;
+    let skew = (Sqrt::sqrt(dimensions + 1.0) - 1.0) / dimensions;
+    let unskew = (1.0 - 1.0 / Sqrt::sqrt(dimensions + 1.0)) / dimensions;
+
+    // Find the simplex cell containing the point, and the offset from its first corner.
+    let skewed = point.iter().sum::<f64>() * skew;
+    let mut cell = [0.0; MAX_DIMENSIONS];
+    for i in 0..n {
+        cell[i] = Round::floor(point[i] + skewed);
+    }
+    let unskewed = cell[..n].iter().sum::<f64>() * unskew;
+    let mut offset = [0.0; MAX_DIMENSIONS];
+    for i in 0..n {
+        offset[i] = point[i] - (cell[i] - unskewed);
+    }
+
+    // Walk the corners of the simplex by stepping along the axes with the largest offset first.
+    let mut order = [0, 1, 2, 3];
+    order[..n].sort_unstable_by(|a, b| offset[*b].total_cmp(&offset[*a]));
+
+    let mut total = 0.0;
+    let mut lattice = cell;
+    for corner in 0..=n {
+        if corner > 0 {
+            lattice[order[corner - 1]] += 1.0;
+        }
+        let mut delta = [0.0; MAX_DIMENSIONS];
+        for i in 0..n {
+            delta[i] = offset[i] - (lattice[i] - cell[i]) + corner as f64 * unskew;
+        }
+
+        let falloff = 0.5 - dot(&delta[..n], &delta[..n]);
+        if falloff > 0.0 {
+            let gradient = gradient(seed, &lattice[..n]);
+            let falloff = falloff * falloff;
+            total += falloff * falloff * dot(&gradient[..n], &delta[..n]);
+        }
+    }
+
+    (total * SIMPLEX_SCALE[n - 1]).clamp(-1.0, 1.0)
+}
+
+// Measured peaks of each noise with unit gradients, per number of dimensions, brought to roughly
+// `[-1, 1]`.
+const PERLIN_SCALE: [f64; MAX_DIMENSIONS] = [2.0, 1.45, 1.5, 1.6];
+const SIMPLEX_SCALE: [f64; MAX_DIMENSIONS] = [68.0, 95.0, 105.0, 105.0];
+
+/// Distance to the nearest feature point, in `[0, 1]`.
+fn worley(seed: u64, point: &[f64]) -> f64 {
+    let n = point.len();
+    let mut cell = [0.0; MAX_DIMENSIONS];
+    for i in 0..n {
+        cell[i] = Round::floor(point[i]);
+    }
+
+    let mut nearest = f64::INFINITY;
+    for neighbor in 0..3usize.pow(n as u32) {
+        // Each neighbor is one of the 3^n cells surrounding the point, one feature point each.
+        let mut lattice = cell;
+        let mut index = neighbor;
+        for component in &mut lattice[..n] {
+            *component += (index % 3) as f64 - 1.0;
+            index /= 3;
+        }
+
+        let mut hash = hash_position(seed, &lattice[..n]);
+        let mut distance = 0.0;
+        for i in 0..n {
+            hash = mix(hash);
+            let feature = lattice[i] + unit(hash);
+            distance += (feature - point[i]) * (feature - point[i]);
+        }
+        nearest = nearest.min(distance);
+    }
+
+    Sqrt::sqrt(nearest).min(1.0)
+}
@@ -5,6 +5,54 @@ use crate::shader::{Shader, Vertex, VertexDim};
 #[cfg(feature = "alloc")]
 use alloc::{boxed::Box, fmt::Debug};
 
+/// Number of fragments held at a time in the stack buffers used to batch-shade.
+pub(crate) const BATCH_CHUNK: usize = 32;
+
+/// Batch-shades `frags` with `shade_batch` after passing each of them through `map`.
+///
+/// The mapped fragments go through a fixed-size stack buffer, so this does not allocate.
+fn batch_mapped<V: Vertex, W: Vertex, O>(
+    frags: &[V],
+    out: &mut [O],
+    map: impl Fn(V) -> W,
+    shade_batch: impl Fn(&[W], &mut [O]),
+) {
+    let Some(&first) = frags.first() else {
+        return;
+    };
+    let mut buffer = [map(first); BATCH_CHUNK];
+
+    for (frags, out) in frags.chunks(BATCH_CHUNK).zip(out.chunks_mut(BATCH_CHUNK)) {
+        let len = frags.len().min(out.len());
+        for (mapped, frag) in buffer.iter_mut().zip(frags) {
+            *mapped = map(*frag);
+        }
+        shade_batch(&buffer[..len], &mut out[..len]);
+    }
+}
+
+/// [`Shader::shade_batch`] of `shader` on the fragments passed through `map`.
+pub(super) fn shade_batch_mapped<V: Vertex, W: Vertex, S: Shader<W>>(
+    shader: &S,
+    frags: &[V],
+    out: &mut [S::Output],
+    map: impl Fn(V) -> W,
+) {
+    batch_mapped(frags, out, map, |frags, out| shader.shade_batch(frags, out));
+}
+
+/// [`Shader::shade_batch_linear`] of `shader` on the fragments passed through `map`.
+pub(super) fn shade_batch_linear_mapped<V: Vertex, W: Vertex, S: Shader<W>>(
+    shader: &S,
+    frags: &[V],
+    out: &mut [LinSrgb<f64>],
+    map: impl Fn(V) -> W,
+) {
+    batch_mapped(frags, out, map, |frags, out| {
+        shader.shade_batch_linear(frags, out)
+    });
+}
+
 pub struct Interpolate<S: Shader<F>, E: Shader<F>, F: Vertex> {
     start: S,
     end: E,
@@ -21,6 +69,24 @@ impl<S: Shader<F>, E: Shader<F>, F: Vertex> Shader<F> for Interpolate<S, E, F> {
 
         start.mix(end, factor)
     }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        let mut ends = [LinSrgb::new(0.0, 0.0, 0.0); BATCH_CHUNK];
+
+        for (frags, out) in frags.chunks(BATCH_CHUNK).zip(out.chunks_mut(BATCH_CHUNK)) {
+            let len = frags.len().min(out.len());
+            self.start
+                .shade_batch_linear(&frags[..len], &mut out[..len]);
+            self.end.shade_batch_linear(&frags[..len], &mut ends[..len]);
+            for ((start, end), frag) in out.iter_mut().zip(ends).zip(frags) {
+                *start = start.mix(end, (self.interpolator)(*frag));
+            }
+        }
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        self.shade_batch(frags, out);
+    }
 }
 
 #[cfg(feature = "alloc")]
@@ -45,6 +111,19 @@ pub fn mix<F: Vertex, S: Shader<F>, E: Shader<F>>(
     }
 }
 
+/// Like [`mix`], but with a factor that varies per fragment, such as [`super::Noise::field`].
+pub fn mix_by<F: Vertex, S: Shader<F>, E: Shader<F>, I: Fn(F) -> f64 + Send + Sync + 'static>(
+    start: S,
+    end: E,
+    factor: I,
+) -> Interpolate<S, E, F> {
+    Interpolate {
+        start,
+        end,
+        interpolator: Box::new(factor),
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct RotateHue<F: Vertex, S: Shader<F>> {
     _marker: core::marker::PhantomData<fn(F)>,
@@ -110,17 +189,33 @@ pub struct ModPosition<S: Shader<F>, M: ToPrimitive, F: Vertex> {
     modulo: M,
 }
 
+impl<V: Vertex, S: Shader<V>, M: ToPrimitive> ModPosition<S, M, V> {
+    /// Maps fragments into the modulo, which is converted once for all of them.
+    fn mapper(&self) -> impl Fn(V) -> V {
+        let modulo = self
+            .modulo
+            .to_f64()
+            .expect("Could not convert modulo type to f64.");
+        move |mut frag| {
+            frag.pos_mut().iter_mut().for_each(|p| *p %= modulo);
+            frag
+        }
+    }
+}
+
 impl<V: Vertex, S: Shader<V>, M: ToPrimitive + Send + Sync> Shader<V> for ModPosition<S, M, V> {
     type Output = S::Output;
 
-    fn shade(&self, mut frag: V) -> Self::Output {
-        frag.pos_mut().iter_mut().for_each(|p| {
-            *p %= self
-                .modulo
-                .to_f64()
-                .expect("Could not convert modulo type to f64.");
-        });
-        self.shader.shade(frag)
+    fn shade(&self, frag: V) -> Self::Output {
+        self.shader.shade(self.mapper()(frag))
+    }
+
+    fn shade_batch(&self, frags: &[V], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, self.mapper());
+    }
+
+    fn shade_batch_linear(&self, frags: &[V], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, self.mapper());
     }
 }
 
@@ -142,17 +237,33 @@ pub struct ModTime<F: Vertex, S: Shader<F>, M: ToPrimitive> {
     modulo: M,
 }
 
+impl<F: Vertex, S: Shader<F>, M: ToPrimitive> ModTime<F, S, M> {
+    /// Maps fragments into the modulo, which is converted once for all of them.
+    fn mapper(&self) -> impl Fn(F) -> F {
+        let modulo = self
+            .modulo
+            .to_f64()
+            .expect("Could not convert modulo type to f64.");
+        move |mut frag| {
+            *frag.time_mut() = frag.time() % modulo;
+            frag
+        }
+    }
+}
+
 impl<F: Vertex, S: Shader<F>, M: ToPrimitive + Send + Sync> Shader<F> for ModTime<F, S, M> {
     type Output = S::Output;
 
-    fn shade(&self, mut frag: F) -> Self::Output {
-        *frag.time_mut() = frag.time()
-            % self
-                .modulo
-                .to_f64()
-                .expect("Could not convert modulo type to f64.");
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.mapper()(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, self.mapper());
+    }
 
-        self.shader.shade(frag)
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, self.mapper());
     }
 }
 
@@ -164,10 +275,29 @@ pub fn mod_time<F: Vertex, S: Shader<F>, M: ToPrimitive>(shader: S, modulo: M) -
     }
 }
 
+/// Shades fragments with one more dimension than `S` by dropping one axis of their position, so
+/// the pattern is stretched out along that axis.
 #[derive(Debug, Clone, Copy)]
 pub struct Extrude<const D: usize, F: Vertex, S: Shader<F>> {
     _marker: core::marker::PhantomData<fn(F)>,
     shader: S,
+    axis: usize,
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Extrude<D, F, S> {
+    fn project<V: VertexDim<{ D + 1 }> + Into<F>>(&self, frag: V) -> F {
+        let mut projected: F = frag.into();
+        let remaining = frag
+            .pos_sized()
+            .iter()
+            .enumerate()
+            .filter(|(axis, _)| *axis != self.axis)
+            .map(|(_, component)| *component);
+        for (target, component) in projected.pos_sized_mut().iter_mut().zip(remaining) {
+            *target = component;
+        }
+        projected
+    }
 }
 
 impl<
@@ -180,15 +310,64 @@ impl<
     type Output = S::Output;
 
     fn shade(&self, frag: VEnd) -> Self::Output {
-        let new_frag: VStart = frag.into();
-        self.shader.shade(new_frag)
+        self.shader.shade(self.project(frag))
+    }
+
+    fn shade_batch(&self, frags: &[VEnd], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.project(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[VEnd], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.project(frag));
     }
 }
 
+/// Extrudes along the new last axis, e.g. a 1D shader along y on a 2D layout.
 pub fn extrude<const D: usize, F: Vertex, S: Shader<F>>(shader: S) -> Extrude<D, F, S> {
+    extrude_along(shader, D)
+}
+
+/// Extrudes along `axis` of the higher dimensional fragments, shading the rest of the axes in
+/// order. Axes past the last one extrude along the last.
+pub fn extrude_along<const D: usize, F: Vertex, S: Shader<F>>(
+    shader: S,
+    axis: usize,
+) -> Extrude<D, F, S> {
     Extrude {
         _marker: core::marker::PhantomData,
         shader,
+        axis: axis.min(D),
+    }
+}
+
+/// Shades fragments of any type convertible to `F`, e.g. a 3D shader on a 1D strip, which is then
+/// laid along the x axis.
+#[derive(Debug, Clone, Copy)]
+pub struct Embed<F: Vertex, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+}
+
+impl<V: Vertex + Into<F>, F: Vertex, S: Shader<F>> Shader<V> for Embed<F, S> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: V) -> Self::Output {
+        self.shader.shade(frag.into())
+    }
+
+    fn shade_batch(&self, frags: &[V], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, Into::into);
+    }
+
+    fn shade_batch_linear(&self, frags: &[V], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, Into::into);
+    }
+}
+
+pub fn embed<F: Vertex, S: Shader<F>>(shader: S) -> Embed<F, S> {
+    Embed {
+        _marker: core::marker::PhantomData,
+        shader,
     }
 }
 
@@ -199,12 +378,26 @@ pub struct ScaleTime<F: Vertex, S: Shader<F>> {
     scale: f64,
 }
 
+impl<F: Vertex, S: Shader<F>> ScaleTime<F, S> {
+    fn map(&self, mut frag: F) -> F {
+        *frag.time_mut() *= self.scale;
+        frag
+    }
+}
+
 impl<F: Vertex, S: Shader<F>> Shader<F> for ScaleTime<F, S> {
     type Output = S::Output;
 
-    fn shade(&self, mut frag: F) -> Self::Output {
-        *frag.time_mut() *= self.scale;
-        self.shader.shade(frag)
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
     }
 }
 
@@ -217,25 +410,110 @@ pub fn scale_time<F: Vertex, S: Shader<F>>(shader: S, scale: f64) -> ScaleTime<F
 }
 
 #[derive(Debug, Clone, Copy)]
-pub struct ScalePosition<F: Vertex, S: Shader<F>> {
+pub struct RemapTime<F: Vertex, S: Shader<F>, E: Fn(f64) -> f64 + Send + Sync> {
     _marker: core::marker::PhantomData<fn(F)>,
     shader: S,
-    scale: f64,
+    remap: E,
+}
+
+impl<F: Vertex, S: Shader<F>, E: Fn(f64) -> f64 + Send + Sync> RemapTime<F, S, E> {
+    fn map(&self, mut frag: F) -> F {
+        *frag.time_mut() = (self.remap)(frag.time());
+        frag
+    }
 }
 
-impl<F: Vertex, S: Shader<F>> Shader<F> for ScalePosition<F, S> {
+impl<F: Vertex, S: Shader<F>, E: Fn(f64) -> f64 + Send + Sync> Shader<F> for RemapTime<F, S, E> {
     type Output = S::Output;
 
-    fn shade(&self, mut frag: F) -> Self::Output {
-        let position = frag.pos_mut();
-        for part in position.iter_mut() {
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+/// Passes the fragment time through `remap`, such as one of the curves in [`crate::easing`].
+///
+/// Easing curves expect a time from 0 to 1, so they are usually wrapped in [`mod_time`] and
+/// [`scale_time`] to ease through each period.
+pub fn remap_time<F: Vertex, S: Shader<F>, E: Fn(f64) -> f64 + Send + Sync>(
+    shader: S,
+    remap: E,
+) -> RemapTime<F, S, E> {
+    RemapTime {
+        _marker: core::marker::PhantomData,
+        shader,
+        remap,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScalePosition<F: Vertex, S: Shader<F>, O = f64> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    scale: O,
+}
+
+impl<F: Vertex, S: Shader<F>> ScalePosition<F, S, f64> {
+    fn map(&self, mut frag: F) -> F {
+        for part in frag.pos_mut().iter_mut() {
             *part *= self.scale;
         }
-        self.shader.shade(frag)
+        frag
+    }
+}
+
+impl<F: Vertex, S: Shader<F>> Shader<F> for ScalePosition<F, S, f64> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> ScalePosition<F, S, [f64; D]> {
+    fn map(&self, mut frag: F) -> F {
+        for (part, scale) in frag.pos_sized_mut().iter_mut().zip(self.scale) {
+            *part *= scale;
+        }
+        frag
+    }
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Shader<F> for ScalePosition<F, S, [f64; D]> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
     }
 }
 
-pub fn scale_position<F: Vertex, S: Shader<F>>(shader: S, scale: f64) -> ScalePosition<F, S> {
+/// Multiplies the fragment position by `scale`, either an `f64` for every axis or an `[f64; D]`
+/// with one factor per axis.
+pub fn scale_position<F: Vertex, O, S: Shader<F>>(shader: S, scale: O) -> ScalePosition<F, S, O> {
     ScalePosition {
         _marker: core::marker::PhantomData,
         shader,
@@ -250,17 +528,60 @@ pub struct TranslatePosition<F: Vertex, S: Shader<F>, O> {
     offset: O,
 }
 
-impl<V: Vertex, S: Shader<V>> Shader<V> for TranslatePosition<V, S, f64> {
-    type Output = S::Output;
-
-    fn shade(&self, mut frag: V) -> Self::Output {
+impl<V: Vertex, S: Shader<V>> TranslatePosition<V, S, f64> {
+    fn map(&self, mut frag: V) -> V {
         frag.pos_mut()
             .iter_mut()
             .for_each(|component| *component += self.offset);
-        self.shader.shade(frag)
+        frag
+    }
+}
+
+impl<V: Vertex, S: Shader<V>> Shader<V> for TranslatePosition<V, S, f64> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: V) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[V], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[V], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+impl<const D: usize, V: VertexDim<D>, S: Shader<V>> TranslatePosition<V, S, [f64; D]> {
+    fn map(&self, mut frag: V) -> V {
+        for (component, offset) in frag.pos_sized_mut().iter_mut().zip(self.offset) {
+            *component += offset;
+        }
+        frag
     }
 }
 
+impl<const D: usize, V: VertexDim<D>, S: Shader<V>> Shader<V>
+    for TranslatePosition<V, S, [f64; D]>
+{
+    type Output = S::Output;
+
+    fn shade(&self, frag: V) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[V], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[V], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+/// Adds `offset` to the fragment position, either an `f64` for every axis or an `[f64; D]` with
+/// one offset per axis.
 pub fn translate_position<F: Vertex, O, S: Shader<F>>(
     shader: S,
     offset: O,
@@ -315,10 +636,7 @@ pub struct VolumeBlur<const P: usize, F: Vertex, S: Shader<F>> {
     radius: f64,
     _marker: core::marker::PhantomData<fn(F)>,
 }
-impl<const P: usize, V: Vertex, S: Shader<V>> Shader<V> for VolumeBlur<P, V, S>
-where
-    S::Output: Clone,
-{
+impl<const P: usize, V: Vertex, S: Shader<V>> Shader<V> for VolumeBlur<P, V, S> {
     type Output = LinSrgb<f64>;
 
     fn shade(&self, mut frag: V) -> Self::Output {
@@ -336,6 +654,43 @@ where
             .fold(LinSrgb::new(0.0, 0.0, 0.0), |acc, c| acc + *c)
             / (colors.len() as f64)
     }
+
+    fn shade_batch(&self, frags: &[V], out: &mut [Self::Output]) {
+        let Some(&first) = frags.first() else {
+            return;
+        };
+        let offsets: [f64; P] =
+            core::array::from_fn(|i| lerp(-self.radius, self.radius, i as f64 / P as f64));
+
+        let mut samples = [first; BATCH_CHUNK];
+        let mut colors = [LinSrgb::new(0.0, 0.0, 0.0); BATCH_CHUNK];
+
+        for (frags, out) in frags.chunks(BATCH_CHUNK).zip(out.chunks_mut(BATCH_CHUNK)) {
+            let len = frags.len().min(out.len());
+            samples[..len].copy_from_slice(&frags[..len]);
+            out[..len].fill(LinSrgb::new(0.0, 0.0, 0.0));
+
+            // Sample the shader at different positions, one offset at a time for the whole chunk
+            for offset in offsets {
+                for sample in &mut samples[..len] {
+                    sample.pos_mut().iter_mut().for_each(|c| *c += offset);
+                }
+                self.shader
+                    .shade_batch_linear(&samples[..len], &mut colors[..len]);
+                for (acc, color) in out[..len].iter_mut().zip(colors) {
+                    *acc += color;
+                }
+            }
+
+            for color in &mut out[..len] {
+                *color /= P as f64;
+            }
+        }
+    }
+
+    fn shade_batch_linear(&self, frags: &[V], out: &mut [LinSrgb<f64>]) {
+        self.shade_batch(frags, out);
+    }
 }
 
 pub fn volume_blur<const P: usize, F: Vertex, S: Shader<F>>(
@@ -1,7 +1,12 @@
-use palette::{IntoColor, LinSrgb, Okhsl, Srgb};
+use palette::{num::Round, IntoColor, LinSrgb, Mix, Okhsl, Oklab, Srgb};
 
 use crate::shader::{Shader, Vertex};
 
+use super::{
+    hash::{hash_position, mix, unit},
+    operation::BATCH_CHUNK,
+};
+
 #[derive(Debug, Clone, Copy)]
 pub struct Checkerboard<F: Vertex, S: Shader<F>, T: Shader<F>> {
     _marker: core::marker::PhantomData<fn(F)>,
@@ -9,23 +14,73 @@ pub struct Checkerboard<F: Vertex, S: Shader<F>, T: Shader<F>> {
     stride: f64,
 }
 
-impl<F: Vertex, S: Shader<F>, T: Shader<F>> Shader<F> for Checkerboard<F, S, T> {
-    type Output = LinSrgb<f64>;
-
-    fn shade(&self, frag: F) -> Self::Output {
-        let first_color = frag
-            .pos()
+impl<F: Vertex, S: Shader<F>, T: Shader<F>> Checkerboard<F, S, T> {
+    fn is_first(&self, frag: &F) -> bool {
+        frag.pos()
             .iter()
             .map(|pos| (pos / self.stride).abs() as usize)
             .sum::<usize>()
-            .is_multiple_of(2);
+            .is_multiple_of(2)
+    }
+}
 
-        if first_color {
+impl<F: Vertex, S: Shader<F>, T: Shader<F>> Shader<F> for Checkerboard<F, S, T> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        if self.is_first(&frag) {
             self.shaders.0.shade(frag).into_color()
         } else {
             self.shaders.1.shade(frag).into_color()
         }
     }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        let Some(&first) = frags.first() else {
+            return;
+        };
+        let mut is_first = [false; BATCH_CHUNK];
+        let (mut firsts, mut seconds) = ([first; BATCH_CHUNK], [first; BATCH_CHUNK]);
+        let mut first_colors = [LinSrgb::new(0.0, 0.0, 0.0); BATCH_CHUNK];
+        let mut second_colors = [LinSrgb::new(0.0, 0.0, 0.0); BATCH_CHUNK];
+
+        for (frags, out) in frags.chunks(BATCH_CHUNK).zip(out.chunks_mut(BATCH_CHUNK)) {
+            let len = frags.len().min(out.len());
+            // Gather the fragments of each color so both shaders are batched, then scatter back.
+            let (mut num_firsts, mut num_seconds) = (0, 0);
+            for (frag, is_first) in frags[..len].iter().zip(&mut is_first) {
+                *is_first = self.is_first(frag);
+                if *is_first {
+                    firsts[num_firsts] = *frag;
+                    num_firsts += 1;
+                } else {
+                    seconds[num_seconds] = *frag;
+                    num_seconds += 1;
+                }
+            }
+
+            self.shaders
+                .0
+                .shade_batch_linear(&firsts[..num_firsts], &mut first_colors[..num_firsts]);
+            self.shaders
+                .1
+                .shade_batch_linear(&seconds[..num_seconds], &mut second_colors[..num_seconds]);
+
+            let (mut first_colors, mut second_colors) = (first_colors.iter(), second_colors.iter());
+            for (out, is_first) in out[..len].iter_mut().zip(is_first) {
+                let colors = if is_first {
+                    &mut first_colors
+                } else {
+                    &mut second_colors
+                };
+                *out = *colors.next().expect("one color per gathered fragment");
+            }
+        }
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        self.shade_batch(frags, out);
+    }
 }
 
 pub fn checkerboard<F: Vertex, S: Shader<F>, T: Shader<F>>(
@@ -66,10 +121,11 @@ impl Clone for Random {
     }
 }
 
+/// The seed [`random`] starts from.
+pub const DEFAULT_RANDOM_SEED: u64 = 0xdeadbeef;
+
 pub fn random() -> Random {
-    Random {
-        seed: portable_atomic::AtomicU64::new(0xdeadbeef),
-    }
+    random_with_seed(DEFAULT_RANDOM_SEED)
 }
 pub fn random_with_seed(seed: u64) -> Random {
     Random {
@@ -77,6 +133,66 @@ pub fn random_with_seed(seed: u64) -> Random {
     }
 }
 
+/// Random colors derived from a hash of the fragment position, so every pixel keeps its color no
+/// matter how often or in what order it is shaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HashedRandom {
+    seed: u64,
+    period: Option<f64>,
+    staggered: bool,
+}
+impl<F: Vertex> Shader<F> for HashedRandom {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let mut hash = hash_position(self.seed, frag.pos());
+
+        if let Some(period) = self.period {
+            // Offsetting each pixel by a random part of the period spreads out when they change.
+            let phase = if self.staggered {
+                unit(mix(hash)) * period
+            } else {
+                0.0
+            };
+            let bucket = Round::floor((frag.time() + phase) / period);
+            hash = mix(hash ^ (bucket + 0.0).to_bits());
+        }
+
+        let red = mix(hash);
+        let green = mix(red);
+        let blue = mix(green);
+        Srgb::new(unit(red), unit(green), unit(blue)).into_color()
+    }
+}
+
+/// A random color per pixel that never changes.
+pub fn hashed_random(seed: u64) -> HashedRandom {
+    HashedRandom {
+        seed,
+        period: None,
+        staggered: false,
+    }
+}
+
+/// A random color per pixel that every pixel picks anew once every `period` of fragment time.
+pub fn hashed_random_every(seed: u64, period: f64) -> HashedRandom {
+    HashedRandom {
+        seed,
+        period: Some(period),
+        staggered: false,
+    }
+}
+
+/// Like [`hashed_random_every`], but each pixel changes at its own point in the period, for
+/// twinkling effects.
+pub fn twinkle(seed: u64, period: f64) -> HashedRandom {
+    HashedRandom {
+        seed,
+        period: Some(period),
+        staggered: true,
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Rainbow<F: Vertex, S: Fn(F) -> f64 + Send + Sync> {
     _marker: core::marker::PhantomData<fn(F)>,
@@ -111,3 +227,156 @@ pub fn position_rainbow<F: Vertex>() -> Rainbow<F, impl Fn(F) -> f64> {
         selector: |frag| frag.pos().iter().sum(),
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    pub position: f64,
+    pub color: LinSrgb<f64>,
+}
+
+pub fn stop(position: f64, color: impl IntoColor<LinSrgb<f64>>) -> ColorStop {
+    ColorStop {
+        position,
+        color: color.into_color(),
+    }
+}
+
+/// The color space colors are blended in between stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientSpace {
+    #[default]
+    LinearSrgb,
+    /// Perceptually even blends.
+    Oklab,
+    /// Blends around the hue wheel, which keeps saturation between distant hues.
+    Okhsl,
+}
+
+/// What happens to selector values outside of `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientExtend {
+    /// Use the color of the nearest end.
+    #[default]
+    Clamp,
+    /// Repeat the gradient, blending from the last stop back into the first.
+    Wrap,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PaletteGradient<F: Vertex, S: Fn(F) -> f64 + Send + Sync, P: AsRef<[ColorStop]>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    selector: S,
+    stops: P,
+    space: GradientSpace,
+    extend: GradientExtend,
+}
+
+impl<F: Vertex, S: Fn(F) -> f64 + Send + Sync, P: AsRef<[ColorStop]>> PaletteGradient<F, S, P> {
+    fn blend(&self, start: LinSrgb<f64>, end: LinSrgb<f64>, factor: f64) -> LinSrgb<f64> {
+        match self.space {
+            GradientSpace::LinearSrgb => start.mix(end, factor),
+            GradientSpace::Oklab => {
+                let start: Oklab<f64> = start.into_color();
+                start.mix(end.into_color(), factor).into_color()
+            }
+            GradientSpace::Okhsl => {
+                let start: Okhsl<f64> = start.into_color();
+                start.mix(end.into_color(), factor).into_color()
+            }
+        }
+    }
+
+    /// Looks up the color at `t`, in the coordinates of the stop positions.
+    pub fn sample(&self, t: f64) -> LinSrgb<f64> {
+        let stops = self.stops.as_ref();
+        let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+            return LinSrgb::new(0.0, 0.0, 0.0);
+        };
+        if stops.len() == 1 || !t.is_finite() {
+            return first.color;
+        }
+
+        let t = match self.extend {
+            GradientExtend::Clamp => t,
+            GradientExtend::Wrap => t - Round::floor(t),
+        };
+
+        // Before the first or after the last stop, either hold the end color or blend across
+        // the seam to the other end.
+        let seam = |t: f64| {
+            let length = first.position + 1.0 - last.position;
+            if length <= 0.0 {
+                return last.color;
+            }
+            self.blend(last.color, first.color, t / length)
+        };
+        if t <= first.position {
+            return match self.extend {
+                GradientExtend::Clamp => first.color,
+                GradientExtend::Wrap => seam(t + 1.0 - last.position),
+            };
+        }
+        if t >= last.position {
+            return match self.extend {
+                GradientExtend::Clamp => last.color,
+                GradientExtend::Wrap => seam(t - last.position),
+            };
+        }
+
+        // Clamped in case stops with a NaN position break the ordering.
+        let end = stops
+            .partition_point(|stop| stop.position <= t)
+            .clamp(1, stops.len() - 1);
+        let (start, end) = (stops[end - 1], stops[end]);
+        let length = end.position - start.position;
+        if length <= 0.0 {
+            return end.color;
+        }
+        self.blend(start.color, end.color, (t - start.position) / length)
+    }
+}
+
+impl<F: Vertex, S: Fn(F) -> f64 + Send + Sync, P: AsRef<[ColorStop]> + Send + Sync> Shader<F>
+    for PaletteGradient<F, S, P>
+{
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.sample((self.selector)(frag))
+    }
+}
+
+/// Maps `selector` onto a gradient through `stops`, which are sorted by position. Stops at the
+/// same position keep their order, making a hard edge. Stop positions are usually within
+/// `[0, 1]`, which is the range repeated by [`GradientExtend::Wrap`]. Selectors that return NaN
+/// or infinity get the color of the first stop.
+///
+/// `stops` can be an array, so no allocation is needed, or any other owned list such as a `Vec`.
+pub fn palette_gradient<
+    F: Vertex,
+    S: Fn(F) -> f64 + Send + Sync,
+    P: AsRef<[ColorStop]> + AsMut<[ColorStop]>,
+>(
+    selector: S,
+    mut stops: P,
+    space: GradientSpace,
+    extend: GradientExtend,
+) -> PaletteGradient<F, S, P> {
+    // An insertion sort, since it is stable and there are only ever a few stops.
+    let list = stops.as_mut();
+    for i in 1..list.len() {
+        let mut j = i;
+        while j > 0 && list[j - 1].position > list[j].position {
+            list.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+
+    PaletteGradient {
+        _marker: core::marker::PhantomData,
+        selector,
+        stops,
+        space,
+        extend,
+    }
+}
@@ -0,0 +1,265 @@
+use palette::{IntoColor, LinSrgb, Mix};
+use portable_atomic::{fence, AtomicU64, AtomicUsize, Ordering};
+
+use crate::shader::{Shader, Vertex};
+
+/// Shades with one of `N` shaders, chosen at runtime with [`Select::switch_to`].
+///
+/// Switching only touches atomics, so it is safe to do from another thread while frames are being
+/// shaded, e.g. whenever the robot changes mode.
+#[derive(Debug)]
+pub struct Select<F: Vertex, S: Shader<F>, const N: usize> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shaders: [S; N],
+    // Seqlock over the switch state below: odd while it is being written, and bumped by 2 after
+    // every write so readers can tell they saw a consistent state.
+    sequence: AtomicUsize,
+    current: AtomicUsize,
+    previous: AtomicUsize,
+    // Bits of the fragment time the last switch was first seen at, or NaN until a fragment has
+    // been shaded since switching.
+    switched_at: AtomicU64,
+    crossfade: f64,
+}
+
+/// The switch state of a [`Select`] as of one value of its sequence.
+#[derive(Debug, Clone, Copy)]
+struct Snapshot {
+    sequence: usize,
+    current: usize,
+    previous: usize,
+    switched_at: f64,
+}
+
+impl<F: Vertex, S: Shader<F>, const N: usize> Select<F, S, N> {
+    /// Switches to the shader at `index`, which can be any type convertible to an index, such as
+    /// an enum of robot modes.
+    ///
+    /// # Panics
+    /// If `index` is not less than `N`.
+    pub fn switch_to(&self, index: impl Into<usize>) {
+        let index = index.into();
+        assert!(
+            index < N,
+            "Shader index {index} is out of range for {N} shaders."
+        );
+
+        loop {
+            let snapshot = self.snapshot();
+            if snapshot.current == index {
+                return;
+            }
+            if self.lock(snapshot.sequence) {
+                self.previous.store(snapshot.current, Ordering::Relaxed);
+                self.current.store(index, Ordering::Relaxed);
+                self.switched_at
+                    .store(f64::NAN.to_bits(), Ordering::Relaxed);
+                self.unlock(snapshot.sequence);
+                return;
+            }
+        }
+    }
+
+    /// Index of the shader currently switched to.
+    pub fn current(&self) -> usize {
+        self.snapshot().current
+    }
+
+    /// Reads the switch state, waiting out any write in progress.
+    fn snapshot(&self) -> Snapshot {
+        loop {
+            let sequence = self.sequence.load(Ordering::Acquire);
+            if sequence % 2 == 1 {
+                core::hint::spin_loop();
+                continue;
+            }
+
+            let snapshot = Snapshot {
+                sequence,
+                current: self.current.load(Ordering::Relaxed),
+                previous: self.previous.load(Ordering::Relaxed),
+                switched_at: f64::from_bits(self.switched_at.load(Ordering::Relaxed)),
+            };
+            fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == sequence {
+                return snapshot;
+            }
+        }
+    }
+
+    /// Starts writing the switch state if nothing changed it since `sequence` was read.
+    fn lock(&self, sequence: usize) -> bool {
+        let locked = self
+            .sequence
+            .compare_exchange(
+                sequence,
+                sequence.wrapping_add(1),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+        fence(Ordering::Release);
+        locked
+    }
+
+    fn unlock(&self, sequence: usize) {
+        self.sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+    }
+
+    /// The switch state, with the start of the crossfade set to `time` if this is the first
+    /// fragment shaded since switching.
+    fn transition(&self, time: f64) -> Snapshot {
+        loop {
+            let snapshot = self.snapshot();
+            if !snapshot.switched_at.is_nan() || snapshot.previous == snapshot.current {
+                return snapshot;
+            }
+            if self.lock(snapshot.sequence) {
+                self.switched_at.store(time.to_bits(), Ordering::Relaxed);
+                self.unlock(snapshot.sequence);
+                return Snapshot {
+                    switched_at: time,
+                    ..snapshot
+                };
+            }
+        }
+    }
+}
+
+impl<F: Vertex, S: Shader<F>, const N: usize> Shader<F> for Select<F, S, N> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        if self.crossfade <= 0.0 {
+            return self.shaders[self.current()].shade(frag).into_color();
+        }
+
+        let Snapshot {
+            current,
+            previous,
+            switched_at,
+            ..
+        } = self.transition(frag.time());
+        let color = self.shaders[current].shade(frag).into_color();
+        if previous == current {
+            return color;
+        }
+
+        let progress = (frag.time() - switched_at) / self.crossfade;
+        if progress >= 1.0 {
+            return color;
+        }
+
+        let previous_color: LinSrgb<f64> = self.shaders[previous].shade(frag).into_color();
+        previous_color.mix(color, progress.max(0.0))
+    }
+}
+
+impl<F: Vertex, S: Shader<F> + Clone, const N: usize> Clone for Select<F, S, N> {
+    fn clone(&self) -> Self {
+        let snapshot = self.snapshot();
+        Select {
+            _marker: core::marker::PhantomData,
+            shaders: self.shaders.clone(),
+            sequence: AtomicUsize::new(0),
+            current: AtomicUsize::new(snapshot.current),
+            previous: AtomicUsize::new(snapshot.previous),
+            switched_at: AtomicU64::new(snapshot.switched_at.to_bits()),
+            crossfade: self.crossfade,
+        }
+    }
+}
+
+/// Switches between `shaders` instantly, starting with the first one.
+pub fn select<F: Vertex, S: Shader<F>, const N: usize>(shaders: [S; N]) -> Select<F, S, N> {
+    select_with_crossfade(shaders, 0.0)
+}
+
+/// Like [`select`], but crossfades from the old shader to the new one over `duration`, measured
+/// in fragment time from the first fragment shaded after switching.
+pub fn select_with_crossfade<F: Vertex, S: Shader<F>, const N: usize>(
+    shaders: [S; N],
+    duration: f64,
+) -> Select<F, S, N> {
+    assert!(N > 0, "Select needs at least one shader.");
+
+    Select {
+        _marker: core::marker::PhantomData,
+        shaders,
+        sequence: AtomicUsize::new(0),
+        current: AtomicUsize::new(0),
+        previous: AtomicUsize::new(0),
+        switched_at: AtomicU64::new(f64::NAN.to_bits()),
+        crossfade: duration,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use palette::LinSrgb;
+
+    use super::select_with_crossfade;
+    use crate::shader::{primitives::color, FragOne, Shader};
+
+    #[test]
+    fn select_crossfade() {
+        #[derive(Clone, Copy)]
+        enum Mode {
+            Disabled,
+            Teleop,
+        }
+        impl From<Mode> for usize {
+            fn from(mode: Mode) -> usize {
+                mode as usize
+            }
+        }
+
+        let shader = select_with_crossfade(
+            [
+                color(LinSrgb::new(1.0, 0.0, 0.0)),
+                color(LinSrgb::new(0.0, 0.0, 1.0)),
+            ],
+            2.0,
+        );
+        let frag = |time| FragOne { pos: [0.0], time };
+        assert_eq!(shader.shade(frag(0.0)), LinSrgb::new(1.0, 0.0, 0.0));
+
+        shader.switch_to(Mode::Teleop);
+        assert_eq!(shader.shade(frag(10.0)), LinSrgb::new(1.0, 0.0, 0.0));
+        assert_eq!(shader.shade(frag(11.0)), LinSrgb::new(0.5, 0.0, 0.5));
+        assert_eq!(shader.shade(frag(12.0)), LinSrgb::new(0.0, 0.0, 1.0));
+
+        shader.switch_to(Mode::Disabled);
+        assert_eq!(shader.current(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn switch_while_shading() {
+        let shader = select_with_crossfade(
+            [
+                color(LinSrgb::new(1.0, 0.0, 0.0)),
+                color(LinSrgb::new(0.0, 1.0, 0.0)),
+                color(LinSrgb::new(0.0, 0.0, 1.0)),
+            ],
+            0.5,
+        );
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for i in 0..10_000usize {
+                    shader.switch_to(i % 3);
+                }
+            });
+            for i in 0..10_000 {
+                let color = shader.shade(FragOne {
+                    pos: [0.0],
+                    time: i as f64 / 100.0,
+                });
+                // Crossfades only ever mix two shaders, so the channels still add up to one.
+                assert!((color.red + color.green + color.blue - 1.0).abs() < 1e-9);
+            }
+        });
+    }
+}
@@ -0,0 +1,171 @@
+use palette::{
+    num::{Sqrt, Trigonometry},
+    LinSrgb,
+};
+
+use crate::shader::{Shader, VertexDim};
+
+use super::operation::{shade_batch_linear_mapped, shade_batch_mapped};
+
+/// A linear map followed by a translation, applied to `D` dimensional positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine<const D: usize> {
+    /// Row-major linear part of the transform.
+    pub matrix: [[f64; D]; D],
+    pub offset: [f64; D],
+}
+
+impl<const D: usize> Affine<D> {
+    pub fn identity() -> Self {
+        let mut matrix = [[0.0; D]; D];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        Affine {
+            matrix,
+            offset: [0.0; D],
+        }
+    }
+
+    pub fn translation(offset: [f64; D]) -> Self {
+        Affine {
+            offset,
+            ..Self::identity()
+        }
+    }
+
+    pub fn scale(scale: [f64; D]) -> Self {
+        let mut matrix = [[0.0; D]; D];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = scale[i];
+        }
+        Affine {
+            matrix,
+            offset: [0.0; D],
+        }
+    }
+
+    pub fn apply(&self, point: [f64; D]) -> [f64; D] {
+        let mut result = self.offset;
+        for (result, row) in result.iter_mut().zip(&self.matrix) {
+            *result += row.iter().zip(&point).map(|(a, b)| a * b).sum::<f64>();
+        }
+        result
+    }
+
+    /// The transform that applies `self` and then `next`.
+    pub fn then(&self, next: &Affine<D>) -> Self {
+        let mut matrix = [[0.0; D]; D];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..D).map(|k| next.matrix[i][k] * self.matrix[k][j]).sum();
+            }
+        }
+        Affine {
+            matrix,
+            offset: next.apply(self.offset),
+        }
+    }
+}
+
+impl<const D: usize> Default for Affine<D> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Affine<2> {
+    /// Counterclockwise rotation about the origin by `angle` degrees.
+    pub fn rotation(angle: f64) -> Self {
+        let (sin, cos) = sin_cos(angle);
+        Affine {
+            matrix: [[cos, -sin], [sin, cos]],
+            offset: [0.0; 2],
+        }
+    }
+}
+
+impl Affine<3> {
+    /// Rotation about `axis` through the origin by `angle` degrees, counterclockwise when looking
+    /// down the axis towards the origin. The axis does not need to be normalized.
+    pub fn rotation(axis: [f64; 3], angle: f64) -> Self {
+        let length = Sqrt::sqrt(axis.iter().map(|a| a * a).sum::<f64>());
+        if length == 0.0 {
+            return Self::identity();
+        }
+        let [x, y, z] = axis.map(|a| a / length);
+        let (sin, cos) = sin_cos(angle);
+        let t = 1.0 - cos;
+
+        Affine {
+            matrix: [
+                [cos + x * x * t, x * y * t - z * sin, x * z * t + y * sin],
+                [y * x * t + z * sin, cos + y * y * t, y * z * t - x * sin],
+                [z * x * t - y * sin, z * y * t + x * sin, cos + z * z * t],
+            ],
+            offset: [0.0; 3],
+        }
+    }
+}
+
+fn sin_cos(angle: f64) -> (f64, f64) {
+    let radians = angle.to_radians();
+    (Trigonometry::sin(radians), Trigonometry::cos(radians))
+}
+
+/// Maps the fragment position through an [`Affine`] transform before shading, e.g. from robot
+/// space into the space a pattern was designed in.
+#[derive(Debug, Clone, Copy)]
+pub struct Transform<const D: usize, F: VertexDim<D>, S: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shader: S,
+    transform: Affine<D>,
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Transform<D, F, S> {
+    fn map(&self, mut frag: F) -> F {
+        *frag.pos_sized_mut() = self.transform.apply(*frag.pos_sized());
+        frag
+    }
+}
+
+impl<const D: usize, F: VertexDim<D>, S: Shader<F>> Shader<F> for Transform<D, F, S> {
+    type Output = S::Output;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        self.shader.shade(self.map(frag))
+    }
+
+    fn shade_batch(&self, frags: &[F], out: &mut [Self::Output]) {
+        shade_batch_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+
+    fn shade_batch_linear(&self, frags: &[F], out: &mut [LinSrgb<f64>]) {
+        shade_batch_linear_mapped(&self.shader, frags, out, |frag| self.map(frag));
+    }
+}
+
+pub fn transform<const D: usize, F: VertexDim<D>, S: Shader<F>>(
+    shader: S,
+    transform: Affine<D>,
+) -> Transform<D, F, S> {
+    Transform {
+        _marker: core::marker::PhantomData,
+        shader,
+        transform,
+    }
+}
+
+/// Rotates the fragment position about the origin by `angle` degrees.
+pub fn rotate_2d<F: VertexDim<2>, S: Shader<F>>(shader: S, angle: f64) -> Transform<2, F, S> {
+    transform(shader, Affine::<2>::rotation(angle))
+}
+
+/// Rotates the fragment position about `axis` by `angle` degrees.
+pub fn rotate_3d<F: VertexDim<3>, S: Shader<F>>(
+    shader: S,
+    axis: [f64; 3],
+    angle: f64,
+) -> Transform<3, F, S> {
+    transform(shader, Affine::<3>::rotation(axis, angle))
+}
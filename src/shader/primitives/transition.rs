@@ -0,0 +1,190 @@
+use palette::{IntoColor, LinSrgb, Mix};
+
+use crate::shader::{Shader, Vertex};
+
+use super::hash::{hash_position, unit};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKind {
+    /// Blends the whole layout from the first shader to the second.
+    Fade,
+    /// Sweeps an edge along `axis` from `from` to `to`, revealing the second shader behind it.
+    Wipe { axis: usize, from: f64, to: f64 },
+    /// Switches every pixel to the second shader at a random point during the transition.
+    Dissolve { seed: u64 },
+    /// Like [`TransitionKind::Wipe`], but the second shader slides in along `axis`, pushing the
+    /// first one out ahead of it.
+    Slide { axis: usize, from: f64, to: f64 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Transition<F: Vertex, S: Shader<F>, T: Shader<F>> {
+    _marker: core::marker::PhantomData<fn(F)>,
+    shaders: (S, T),
+    start: f64,
+    duration: f64,
+    kind: TransitionKind,
+}
+
+impl<F: Vertex, S: Shader<F>, T: Shader<F>> Transition<F, S, T> {
+    /// How far through the transition the fragment time is, from 0 to 1.
+    pub fn progress(&self, time: f64) -> f64 {
+        if self.duration <= 0.0 {
+            return if time < self.start { 0.0 } else { 1.0 };
+        }
+        ((time - self.start) / self.duration).clamp(0.0, 1.0)
+    }
+
+    fn first(&self, frag: F) -> LinSrgb<f64> {
+        self.shaders.0.shade(frag).into_color()
+    }
+
+    fn second(&self, frag: F) -> LinSrgb<f64> {
+        self.shaders.1.shade(frag).into_color()
+    }
+}
+
+fn axis_position<F: Vertex>(frag: &F, axis: usize) -> f64 {
+    frag.pos().get(axis).copied().unwrap_or(0.0)
+}
+
+fn offset_axis<F: Vertex>(mut frag: F, axis: usize, offset: f64) -> F {
+    if let Some(component) = frag.pos_mut().get_mut(axis) {
+        *component += offset;
+    }
+    frag
+}
+
+impl<F: Vertex, S: Shader<F>, T: Shader<F>> Shader<F> for Transition<F, S, T> {
+    type Output = LinSrgb<f64>;
+
+    fn shade(&self, frag: F) -> Self::Output {
+        let progress = self.progress(frag.time());
+        if progress <= 0.0 {
+            return self.first(frag);
+        } else if progress >= 1.0 {
+            return self.second(frag);
+        }
+
+        match self.kind {
+            TransitionKind::Fade => self.first(frag).mix(self.second(frag), progress),
+            TransitionKind::Wipe { axis, from, to } => {
+                let edge = from + (to - from) * progress;
+                let position = axis_position(&frag, axis);
+                let revealed = if from <= to {
+                    position < edge
+                } else {
+                    position > edge
+                };
+
+                if revealed {
+                    self.second(frag)
+                } else {
+                    self.first(frag)
+                }
+            }
+            TransitionKind::Dissolve { seed } => {
+                if unit(hash_position(seed, frag.pos())) < progress {
+                    self.second(frag)
+                } else {
+                    self.first(frag)
+                }
+            }
+            TransitionKind::Slide { axis, from, to } => {
+                let length = to - from;
+                let offset = length * progress;
+                let position = axis_position(&frag, axis);
+                let revealed = if from <= to {
+                    position < from + offset
+                } else {
+                    position > from + offset
+                };
+
+                if revealed {
+                    self.second(offset_axis(frag, axis, length - offset))
+                } else {
+                    self.first(offset_axis(frag, axis, -offset))
+                }
+            }
+        }
+    }
+}
+
+/// Transitions from `first` to `second` over `duration`, starting when the fragment time reaches
+/// `start`.
+pub fn transition<F: Vertex, S: Shader<F>, T: Shader<F>>(
+    first: S,
+    second: T,
+    start: f64,
+    duration: f64,
+    kind: TransitionKind,
+) -> Transition<F, S, T> {
+    Transition {
+        _marker: core::marker::PhantomData,
+        shaders: (first, second),
+        start,
+        duration,
+        kind,
+    }
+}
+
+pub fn fade<F: Vertex, S: Shader<F>, T: Shader<F>>(
+    first: S,
+    second: T,
+    start: f64,
+    duration: f64,
+) -> Transition<F, S, T> {
+    transition(first, second, start, duration, TransitionKind::Fade)
+}
+
+pub fn wipe<F: Vertex, S: Shader<F>, T: Shader<F>>(
+    first: S,
+    second: T,
+    start: f64,
+    duration: f64,
+    axis: usize,
+    from: f64,
+    to: f64,
+) -> Transition<F, S, T> {
+    transition(
+        first,
+        second,
+        start,
+        duration,
+        TransitionKind::Wipe { axis, from, to },
+    )
+}
+
+pub fn dissolve<F: Vertex, S: Shader<F>, T: Shader<F>>(
+    first: S,
+    second: T,
+    start: f64,
+    duration: f64,
+    seed: u64,
+) -> Transition<F, S, T> {
+    transition(
+        first,
+        second,
+        start,
+        duration,
+        TransitionKind::Dissolve { seed },
+    )
+}
+
+pub fn slide<F: Vertex, S: Shader<F>, T: Shader<F>>(
+    first: S,
+    second: T,
+    start: f64,
+    duration: f64,
+    axis: usize,
+    from: f64,
+    to: f64,
+) -> Transition<F, S, T> {
+    transition(
+        first,
+        second,
+        start,
+        duration,
+        TransitionKind::Slide { axis, from, to },
+    )
+}